@@ -0,0 +1,290 @@
+use crate::match_result::MatchResult;
+
+/// The identifier of the root state of the automaton.
+const ROOT: usize = 0;
+
+/// Decides which match is reported when several patterns match at the same position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The leftmost match is reported, preferring the pattern given first, like an alternation in a regex.
+    LeftmostFirst,
+    /// The leftmost match is reported, preferring the longest pattern.
+    LeftmostLongest,
+}
+
+/// A state of the automaton, corresponding to a prefix of one or more patterns.
+#[derive(Debug, Clone)]
+struct State {
+    /// The transitions to the next states, sorted by byte.
+    transitions: Vec<(u8, usize)>,
+    /// The state of the longest proper suffix of this prefix that is also a prefix in the automaton.
+    fail: usize,
+    /// The closest state reachable through the fail links whose prefix is a whole pattern.
+    dict: Option<usize>,
+    /// The pattern equal to the prefix of this state, if any.
+    pattern: Option<usize>,
+    /// The length of the prefix of this state.
+    depth: usize,
+}
+
+impl State {
+    fn new(depth: usize) -> State {
+        State {
+            transitions: vec![],
+            fail: ROOT,
+            dict: None,
+            pattern: None,
+            depth,
+        }
+    }
+
+    fn next(&self, byte: u8) -> Option<usize> {
+        self.transitions
+            .binary_search_by_key(&byte, |&(b, _)| b)
+            .ok()
+            .map(|i| self.transitions[i].1)
+    }
+}
+
+/// Builder to configure an `AhoCorasick` automaton before building it.
+#[derive(Debug, Clone)]
+pub struct AhoCorasickBuilder {
+    match_kind: MatchKind,
+    ascii_case_insensitive: bool,
+}
+
+impl Default for AhoCorasickBuilder {
+    fn default() -> Self {
+        AhoCorasickBuilder::new()
+    }
+}
+
+impl AhoCorasickBuilder {
+    /// Creates a builder with leftmost-first semantics and case sensitive matching.
+    pub fn new() -> AhoCorasickBuilder {
+        AhoCorasickBuilder {
+            match_kind: MatchKind::LeftmostFirst,
+            ascii_case_insensitive: false,
+        }
+    }
+
+    /// Sets which match is reported when several patterns match.
+    pub fn match_kind(&mut self, match_kind: MatchKind) -> &mut AhoCorasickBuilder {
+        self.match_kind = match_kind;
+        self
+    }
+
+    /// Enables matching ASCII letters without regard to their case.
+    pub fn ascii_case_insensitive(&mut self, yes: bool) -> &mut AhoCorasickBuilder {
+        self.ascii_case_insensitive = yes;
+        self
+    }
+
+    /// Builds the automaton for the given patterns. Each pattern is identified by its index.
+    pub fn build<I, P>(&self, patterns: I) -> AhoCorasick
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut automaton = AhoCorasick {
+            states: vec![State::new(0)],
+            pattern_lens: vec![],
+            match_kind: self.match_kind,
+            ascii_case_insensitive: self.ascii_case_insensitive,
+        };
+        for pattern in patterns {
+            automaton.add_pattern(pattern.as_ref());
+        }
+        automaton.fill_fail_links();
+        automaton
+    }
+}
+
+/// An Aho-Corasick automaton, searching many literal strings at once in a single pass over the input.
+/// The time of a search doesn't depend on the number of patterns, which makes it the right tool for
+/// long lists of fixed strings.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    states: Vec<State>,
+    pattern_lens: Vec<usize>,
+    match_kind: MatchKind,
+    ascii_case_insensitive: bool,
+}
+
+impl AhoCorasick {
+    /// Creates an automaton with the default configuration of `AhoCorasickBuilder`.
+    pub fn new<I, P>(patterns: I) -> AhoCorasick
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        AhoCorasickBuilder::new().build(patterns)
+    }
+
+    /// Returns the number of patterns in the automaton.
+    pub fn pattern_count(&self) -> usize {
+        self.pattern_lens.len()
+    }
+
+    /// Returns `true` if any pattern occurs in the haystack.
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find_at(haystack, 0).is_some()
+    }
+
+    /// Returns the first match in the value, according to the `MatchKind` of the automaton.
+    pub fn find(&self, value: &str) -> Option<MatchResult> {
+        self.find_at(value.as_bytes(), 0)
+            .map(|(_, start, end)| MatchResult::new(start, end, value[start..end].to_string()))
+    }
+
+    /// Returns an iterator over the successive non-overlapping matches in the value.
+    pub fn find_iter<'a>(&'a self, value: &'a str) -> impl Iterator<Item = MatchResult> + 'a {
+        let mut at = 0;
+        std::iter::from_fn(move || {
+            if at > value.len() {
+                return None;
+            }
+            let (_, start, end) = self.find_at(value.as_bytes(), at)?;
+            at = if end > start {
+                end
+            } else {
+                end + value[end..].chars().next().map_or(1, char::len_utf8)
+            };
+            Some(MatchResult::new(start, end, value[start..end].to_string()))
+        })
+    }
+
+    /// Searches the haystack starting at the position `at`.
+    /// Returns the index of the pattern that matched and the start and end of the match.
+    ///
+    /// The automaton is run until no pattern can start before the best match found so far,
+    /// which happens once the prefix being tracked begins after the start of that match.
+    pub fn find_at(&self, haystack: &[u8], at: usize) -> Option<(usize, usize, usize)> {
+        let mut best: Option<(usize, usize, usize)> = None;
+        let mut state = ROOT;
+        self.update_best(ROOT, at, &mut best);
+        for (i, &byte) in haystack.iter().enumerate().skip(at) {
+            state = self.next_state(state, self.normalize(byte));
+            let end = i + 1;
+            if let Some((_, start, _)) = best {
+                if end - self.states[state].depth > start {
+                    break;
+                }
+            }
+            self.update_best(state, end, &mut best);
+        }
+        best
+    }
+
+    /// Replaces the best match so far with the patterns ending at the given state and position, if they are preferred.
+    fn update_best(&self, state: usize, end: usize, best: &mut Option<(usize, usize, usize)>) {
+        let mut current = if self.states[state].pattern.is_some() {
+            Some(state)
+        } else {
+            self.states[state].dict
+        };
+        while let Some(s) = current {
+            if let Some(pattern) = self.states[s].pattern {
+                let candidate = (pattern, end - self.pattern_lens[pattern], end);
+                if best.is_none_or(|best| self.is_preferred(candidate, best)) {
+                    *best = Some(candidate);
+                }
+            }
+            current = self.states[s].dict;
+        }
+    }
+
+    /// Returns `true` if the candidate match should be reported instead of the current one.
+    fn is_preferred(
+        &self,
+        candidate: (usize, usize, usize),
+        current: (usize, usize, usize),
+    ) -> bool {
+        let (pattern, start, end) = candidate;
+        let (current_pattern, current_start, current_end) = current;
+        if start != current_start {
+            return start < current_start;
+        }
+        match self.match_kind {
+            MatchKind::LeftmostFirst => pattern < current_pattern,
+            MatchKind::LeftmostLongest => {
+                end > current_end || (end == current_end && pattern < current_pattern)
+            }
+        }
+    }
+
+    fn normalize(&self, byte: u8) -> u8 {
+        if self.ascii_case_insensitive {
+            byte.to_ascii_lowercase()
+        } else {
+            byte
+        }
+    }
+
+    fn next_state(&self, state: usize, byte: u8) -> usize {
+        let mut state = state;
+        loop {
+            if let Some(next) = self.states[state].next(byte) {
+                return next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.states[state].fail;
+        }
+    }
+
+    /// Adds a pattern to the trie of the automaton.
+    fn add_pattern(&mut self, pattern: &[u8]) {
+        let index = self.pattern_lens.len();
+        self.pattern_lens.push(pattern.len());
+        let mut state = ROOT;
+        for &byte in pattern {
+            let byte = self.normalize(byte);
+            state = match self.states[state].next(byte) {
+                Some(next) => next,
+                None => {
+                    let next = self.states.len();
+                    self.states.push(State::new(self.states[state].depth + 1));
+                    let transitions = &mut self.states[state].transitions;
+                    let position = transitions.partition_point(|&(b, _)| b < byte);
+                    transitions.insert(position, (byte, next));
+                    next
+                }
+            };
+        }
+        if self.states[state].pattern.is_none() {
+            self.states[state].pattern = Some(index);
+        }
+    }
+
+    /// Computes the fail and dictionary links of every state, visiting the trie in breadth first order.
+    fn fill_fail_links(&mut self) {
+        let mut queue = std::collections::VecDeque::new();
+        for &(_, child) in &self.states[ROOT].transitions {
+            queue.push_back(child);
+        }
+        while let Some(state) = queue.pop_front() {
+            let transitions = self.states[state].transitions.clone();
+            for (byte, child) in transitions {
+                let mut fail = self.states[state].fail;
+                let child_fail = loop {
+                    if let Some(next) = self.states[fail].next(byte) {
+                        break next;
+                    }
+                    if fail == ROOT {
+                        break ROOT;
+                    }
+                    fail = self.states[fail].fail;
+                };
+                self.states[child].fail = child_fail;
+                self.states[child].dict = if self.states[child_fail].pattern.is_some() {
+                    Some(child_fail)
+                } else {
+                    self.states[child_fail].dict
+                };
+                queue.push_back(child);
+            }
+        }
+    }
+}
@@ -1,6 +1,41 @@
+use crate::error::RegexError;
+
 /// A bracket expression represents a posible special value in a regex, that matches any single character in the provided list.
 /// It has a vector of chars representing the characters that can be matched, and a boolean indicating if the expression is negated.
+#[derive(Debug, Clone, PartialEq)]
 pub struct BracketExpression {
     pub chars: Vec<char>,
     pub is_negated: bool,
 }
+
+impl BracketExpression {
+    /// Parses the elements inside a bracket, like `^a-z_`.
+    /// A leading `^` negates the expression, and every `x-y` adds the characters from `x` to `y`.
+    /// Returns an `InvalidBracketRange` error if a range ends before it starts.
+    pub fn parse(expression: &[char]) -> Result<BracketExpression, RegexError> {
+        let (is_negated, expression) = match expression.split_first() {
+            Some(('^', rest)) => (true, rest),
+            _ => (false, expression),
+        };
+        let mut chars: Vec<char> = vec![];
+        let mut i = 0;
+        while i < expression.len() {
+            if i + 2 < expression.len() && expression[i + 1] == '-' {
+                if expression[i] > expression[i + 2] {
+                    return Err(RegexError::InvalidBracketRange);
+                }
+                chars.extend(expression[i]..=expression[i + 2]);
+                i += 3;
+            } else {
+                chars.push(expression[i]);
+                i += 1;
+            }
+        }
+        Ok(BracketExpression { chars, is_negated })
+    }
+
+    /// Checks if the character is one of the list, or isn't if the expression is negated.
+    pub fn matches(&self, c: char) -> bool {
+        self.chars.contains(&c) != self.is_negated
+    }
+}
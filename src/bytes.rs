@@ -0,0 +1,95 @@
+use crate::error::RegexError;
+use crate::regex::{self, ParseOptions};
+use crate::syntax::Syntax;
+use crate::utf8;
+
+/// A regular expression matched against arbitrary bytes instead of strings.
+/// The characters of the expression match their UTF-8 encoding, and `\xNN` escapes above `\x7f` match raw bytes,
+/// so files that aren't valid UTF-8 can still be searched.
+#[derive(Debug)]
+pub struct Regex {
+    regex: regex::Regex,
+}
+
+/// A match of a byte regex, with its range in the haystack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub matched: &'a [u8],
+}
+
+impl Match<'_> {
+    pub fn range(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+}
+
+impl Regex {
+    /// Creates a new byte `Regex` from a given extended regular expression.
+    pub fn new(expression: &str) -> Result<Self, RegexError> {
+        Regex::with_options(expression, Syntax::Extended, false)
+    }
+
+    /// Creates a new byte `Regex` from an expression written in the given dialect.
+    pub fn with_syntax(expression: &str, syntax: Syntax) -> Result<Self, RegexError> {
+        Regex::with_options(expression, syntax, false)
+    }
+
+    /// Creates a new byte `Regex` from an expression written in the given dialect.
+    /// If `dot_matches_any_byte` is `true`, the `.` also matches the bytes that aren't part of a valid
+    /// UTF-8 character, otherwise they are only matched by `\xNN` escapes.
+    pub fn with_options(
+        expression: &str,
+        syntax: Syntax,
+        dot_matches_any_byte: bool,
+    ) -> Result<Self, RegexError> {
+        let options = ParseOptions {
+            bytes: true,
+            dot_matches_any_byte,
+            ..ParseOptions::default()
+        };
+        let regex = regex::Regex::with_options(expression, syntax, options)?;
+        Ok(Regex { regex })
+    }
+
+    /// Returns `true` if the regex matches somewhere in the haystack.
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    /// Returns the leftmost match in the haystack, if any.
+    pub fn find<'a>(&self, haystack: &'a [u8]) -> Option<Match<'a>> {
+        self.find_at(haystack, 0)
+    }
+
+    /// Returns the leftmost match that starts at or after the position `at` of the haystack.
+    /// If the backtracking engine runs out of steps, the match is searched with the linear engine instead.
+    pub fn find_at<'a>(&self, haystack: &'a [u8], at: usize) -> Option<Match<'a>> {
+        let (start, end) = self.regex.search(haystack, at)?;
+        Some(Match {
+            start,
+            end,
+            matched: &haystack[start..end],
+        })
+    }
+
+    /// Returns an iterator over the successive non-overlapping matches in the haystack, from left to right.
+    /// After an empty match the search continues one character later, or one byte later if the haystack
+    /// isn't valid UTF-8 there.
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = Match<'a>> + 'a {
+        let mut at = 0;
+        std::iter::from_fn(move || {
+            if at > haystack.len() {
+                return None;
+            }
+            let found = self.find_at(haystack, at)?;
+            at = if found.end > found.start {
+                found.end
+            } else {
+                found.end + utf8::char_len(&haystack[found.end..])
+            };
+            Some(found)
+        })
+    }
+}
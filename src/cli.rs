@@ -0,0 +1,623 @@
+use crate::error::RegexError;
+use crate::file_handler::{ColorChoice, Report};
+use crate::glob::Glob;
+use crate::matcher::Matcher;
+use crate::parallel;
+use crate::regex::{ParseOptions, Regex, DEFAULT_MATCH_LIMIT};
+use crate::syntax::Syntax;
+use crate::walker::Walker;
+use std::fmt;
+
+/// The usage line printed with the argument errors.
+pub const USAGE: &str = "Usage: grep-rustico [OPTION]... PATTERN [FILE]...";
+
+/// The text printed by `--help`.
+pub const HELP: &str = "\
+Usage: grep-rustico [OPTION]... PATTERN [FILE]...
+Search for PATTERN in each FILE.
+With no FILE, read . when searching recursively, or else the standard input.
+When FILE is -, read the standard input.
+
+Pattern selection and interpretation:
+  -E, --extended-regexp      PATTERN is an extended regular expression (default)
+  -G, --basic-regexp         PATTERN is a basic regular expression
+  -P, --perl-regexp          PATTERN is a Perl compatible regular expression
+  -F, --fixed-strings        PATTERN is a set of newline separated strings
+  -e, --regexp=PATTERN       use PATTERN for matching, can be given several times
+  -i, --ignore-case          ignore case distinctions in patterns and data
+  -U, --multiline            let the matches span several lines
+  -v, --invert-match         select non-matching lines, not with -U
+      --max-errors=EDITS     find approximate matches with at most EDITS edits
+      --match-limit=STEPS    limit the backtracking steps on a line, 0 for no limit
+
+Output control:
+  -c, --count                print only a count of selected lines per FILE
+  -l, --files-with-matches   print only names of FILEs with selected lines
+  -L, --files-without-match  print only names of FILEs with no selected lines
+  -H, --with-filename        print the file name for each match
+  -h, --no-filename          suppress the file name prefix on output
+  -n, --line-number          print line number with output lines
+  -b, --byte-offset          print the byte offset with output lines
+      --column               print the column of the first match
+  -o, --only-matching        show only the part of a line matching PATTERN
+      --vimgrep              print every match as FILE:LINE:COLUMN:LINE
+      --label=LABEL          use LABEL as the standard input file name prefix
+      --replace=TEMPLATE     replace every match with TEMPLATE
+      --color=WHEN           color the output, WHEN is auto, always or never
+
+Context control, not with -U, and ignored with -o and --vimgrep:
+  -B, --before-context=NUM   print NUM lines of leading context
+  -A, --after-context=NUM    print NUM lines of trailing context
+  -C, --context=NUM          print NUM lines of output context
+
+File and directory selection:
+  -r, --recursive            search the files under each directory
+  -R, --dereference-recursive  likewise, but follow all symlinks
+      --max-depth=NUM        descend at most NUM levels of directories
+      --include=GLOB         search only files that match GLOB
+      --exclude=GLOB         skip files that match GLOB
+      --exclude-dir=GLOB     skip directories that match GLOB
+      --no-ignore            don't respect .gitignore, .ignore and .git/info/exclude
+      --hidden               search hidden files and directories
+
+Miscellaneous:
+  -j, --threads=NUM          search NUM files at once, one per core by default
+      --help                 display this help and exit
+  -V, --version              display version information and exit
+";
+
+/// The text printed by `--version`.
+pub const VERSION: &str = concat!("grep-rustico ", env!("CARGO_PKG_VERSION"));
+
+/// The options of a search, parsed from the command line arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The patterns to search for, a line matches if any of them matches it.
+    pub patterns: Vec<String>,
+    pub files: Vec<String>,
+    pub syntax: Syntax,
+    pub fixed_strings: bool,
+    pub case_insensitive: bool,
+    pub multiline: bool,
+    /// Whether the lines without a match are printed instead of the ones with a match.
+    pub invert_match: bool,
+    /// Whether the printed lines are prefixed with the name of their file,
+    /// `None` to prefix them only when several files are searched.
+    pub with_filename: Option<bool>,
+    /// The name shown for the standard input, instead of `(standard input)`.
+    pub label: Option<String>,
+    /// What is printed for every file: its selected lines, their count, or only its name.
+    pub report: Report,
+    /// Whether the printed lines are prefixed with their 1-based number.
+    pub line_number: bool,
+    /// Whether the printed lines are prefixed with their offset in the file, or the one of the match with `-o`.
+    pub byte_offset: bool,
+    /// Whether the printed lines are prefixed with the 1-based column of their first match.
+    pub column: bool,
+    /// Whether only the matches are printed, every one on its own line.
+    pub only_matching: bool,
+    /// Whether every match is printed with its line as `file:line:column:line`, like editors expect.
+    pub vimgrep: bool,
+    /// How many lines are printed before and after every selected line, separated by `--` from the ones that
+    /// don't follow the last printed line.
+    pub before_context: usize,
+    pub after_context: usize,
+    pub replacement: Option<String>,
+    /// When the matches are highlighted and the file names and numbers colored.
+    pub color: ColorChoice,
+    /// The steps the backtracking engine can take on a line, `None` for no limit.
+    pub match_limit: Option<usize>,
+    /// The edits allowed in an approximate match, `None` to only search exact matches.
+    pub max_errors: Option<usize>,
+    /// Whether the directories are searched recursively, instead of being reported as errors.
+    pub recursive: bool,
+    /// Whether the symbolic links found while searching recursively are followed.
+    pub follow_links: bool,
+    /// How many levels of directories the recursive search descends, `None` for no limit.
+    pub max_depth: Option<usize>,
+    /// The files found in the directories are only searched if they match any of these globs, if there is any.
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    pub exclude_dir: Vec<Glob>,
+    /// Whether the ignore files are disregarded when searching recursively, see `Walker::ignore_files`.
+    pub no_ignore: bool,
+    /// Whether the hidden files and directories are searched recursively too.
+    pub hidden: bool,
+    /// How many files are searched at once, `None` for one per core.
+    pub threads: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            patterns: vec![],
+            files: vec![],
+            syntax: Syntax::Extended,
+            fixed_strings: false,
+            case_insensitive: false,
+            multiline: false,
+            invert_match: false,
+            with_filename: None,
+            label: None,
+            report: Report::Lines,
+            line_number: false,
+            byte_offset: false,
+            column: false,
+            only_matching: false,
+            vimgrep: false,
+            before_context: 0,
+            after_context: 0,
+            replacement: None,
+            color: ColorChoice::Auto,
+            match_limit: Some(DEFAULT_MATCH_LIMIT),
+            max_errors: None,
+            recursive: false,
+            follow_links: false,
+            max_depth: None,
+            include: vec![],
+            exclude: vec![],
+            exclude_dir: vec![],
+            no_ignore: false,
+            hidden: false,
+            threads: None,
+        }
+    }
+}
+
+/// What the command line asks the program to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Search(Box<Config>),
+    Help,
+    Version,
+}
+
+/// An error in the command line arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgsError {
+    UnknownOption(String),
+    MissingValue(String),
+    UnexpectedValue(String),
+    InvalidValue { option: String, value: String },
+    MissingPattern,
+    IncompatibleOptions(String, String),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::UnknownOption(option) => write!(f, "unrecognized option '{}'", option),
+            ArgsError::MissingValue(option) => {
+                write!(f, "option '{}' requires an argument", option)
+            }
+            ArgsError::UnexpectedValue(option) => {
+                write!(f, "option '{}' doesn't allow an argument", option)
+            }
+            ArgsError::InvalidValue { option, value } => {
+                write!(f, "invalid argument '{}' for '{}'", value, option)
+            }
+            ArgsError::MissingPattern => write!(f, "no pattern given"),
+            ArgsError::IncompatibleOptions(first, second) => {
+                write!(f, "'{}' can't be used with '{}'", first, second)
+            }
+        }
+    }
+}
+
+/// The options the parser knows about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Opt {
+    Extended,
+    Basic,
+    Perl,
+    FixedStrings,
+    Regexp,
+    IgnoreCase,
+    Multiline,
+    InvertMatch,
+    WithFilename,
+    NoFilename,
+    Recursive,
+    DereferenceRecursive,
+    MaxDepth,
+    Include,
+    Exclude,
+    ExcludeDir,
+    NoIgnore,
+    Hidden,
+    Threads,
+    Label,
+    Count,
+    FilesWithMatches,
+    FilesWithoutMatch,
+    LineNumber,
+    ByteOffset,
+    Column,
+    OnlyMatching,
+    Vimgrep,
+    BeforeContext,
+    AfterContext,
+    Context,
+    MaxErrors,
+    MatchLimit,
+    Replace,
+    Color,
+    Help,
+    Version,
+}
+
+/// The short and long names of every option.
+const OPTIONS: &[(Option<char>, &str, Opt)] = &[
+    (Some('E'), "extended-regexp", Opt::Extended),
+    (Some('G'), "basic-regexp", Opt::Basic),
+    (Some('P'), "perl-regexp", Opt::Perl),
+    (Some('F'), "fixed-strings", Opt::FixedStrings),
+    (Some('e'), "regexp", Opt::Regexp),
+    (Some('i'), "ignore-case", Opt::IgnoreCase),
+    (Some('U'), "multiline", Opt::Multiline),
+    (Some('v'), "invert-match", Opt::InvertMatch),
+    (Some('H'), "with-filename", Opt::WithFilename),
+    (Some('h'), "no-filename", Opt::NoFilename),
+    (None, "label", Opt::Label),
+    (Some('c'), "count", Opt::Count),
+    (Some('l'), "files-with-matches", Opt::FilesWithMatches),
+    (Some('L'), "files-without-match", Opt::FilesWithoutMatch),
+    (Some('n'), "line-number", Opt::LineNumber),
+    (Some('b'), "byte-offset", Opt::ByteOffset),
+    (None, "column", Opt::Column),
+    (Some('o'), "only-matching", Opt::OnlyMatching),
+    (None, "vimgrep", Opt::Vimgrep),
+    (Some('B'), "before-context", Opt::BeforeContext),
+    (Some('A'), "after-context", Opt::AfterContext),
+    (Some('C'), "context", Opt::Context),
+    (Some('r'), "recursive", Opt::Recursive),
+    (
+        Some('R'),
+        "dereference-recursive",
+        Opt::DereferenceRecursive,
+    ),
+    (None, "max-depth", Opt::MaxDepth),
+    (None, "include", Opt::Include),
+    (None, "exclude", Opt::Exclude),
+    (None, "exclude-dir", Opt::ExcludeDir),
+    (None, "no-ignore", Opt::NoIgnore),
+    (None, "hidden", Opt::Hidden),
+    (None, "max-errors", Opt::MaxErrors),
+    (None, "match-limit", Opt::MatchLimit),
+    (None, "replace", Opt::Replace),
+    (None, "color", Opt::Color),
+    (Some('j'), "threads", Opt::Threads),
+    (None, "help", Opt::Help),
+    (Some('V'), "version", Opt::Version),
+];
+
+impl Opt {
+    fn takes_value(self) -> bool {
+        matches!(
+            self,
+            Opt::Regexp
+                | Opt::Label
+                | Opt::MaxDepth
+                | Opt::Include
+                | Opt::Exclude
+                | Opt::ExcludeDir
+                | Opt::Threads
+                | Opt::BeforeContext
+                | Opt::AfterContext
+                | Opt::Context
+                | Opt::MaxErrors
+                | Opt::MatchLimit
+                | Opt::Replace
+                | Opt::Color
+        )
+    }
+
+    fn from_short(name: char) -> Option<Opt> {
+        OPTIONS
+            .iter()
+            .find(|(short, _, _)| *short == Some(name))
+            .map(|(_, _, opt)| *opt)
+    }
+
+    fn from_long(name: &str) -> Option<Opt> {
+        OPTIONS
+            .iter()
+            .find(|(_, long, _)| *long == name)
+            .map(|(_, _, opt)| *opt)
+    }
+}
+
+/// Parses the command line arguments, without the name of the program.
+///
+/// The short options can be grouped in a single argument, like `-iF`, and the value of the last one
+/// can follow it in the same argument or in the next one, like `-epattern` or `-e pattern`.
+/// The long options take their value after a `=` or in the next argument.
+/// Every argument after `--` is positional, and so is `-` alone.
+/// The first positional argument is the pattern unless some were given with `-e`, the others are the files.
+/// Without files, the current directory is searched when recursive, or else the standard input, as the file `-`.
+pub fn parse_args<I, S>(args: I) -> Result<Command, ArgsError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut config = Config::default();
+    let mut positional: Vec<String> = vec![];
+    let mut args = args.into_iter().map(Into::into);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline_value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let option = format!("--{}", name);
+            let opt =
+                Opt::from_long(name).ok_or_else(|| ArgsError::UnknownOption(option.clone()))?;
+            let value = match (opt.takes_value(), inline_value) {
+                (true, Some(value)) => Some(value),
+                (true, None) => Some(args.next().ok_or(ArgsError::MissingValue(option.clone()))?),
+                (false, Some(_)) => return Err(ArgsError::UnexpectedValue(option)),
+                (false, None) => None,
+            };
+            if let Some(command) = apply(&mut config, opt, &option, value)? {
+                return Ok(command);
+            }
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            for (i, name) in arg.char_indices().skip(1) {
+                let option = format!("-{}", name);
+                let opt = Opt::from_short(name)
+                    .ok_or_else(|| ArgsError::UnknownOption(option.clone()))?;
+                let value = if opt.takes_value() {
+                    let rest = &arg[i + name.len_utf8()..];
+                    Some(if rest.is_empty() {
+                        args.next().ok_or(ArgsError::MissingValue(option.clone()))?
+                    } else {
+                        rest.to_string()
+                    })
+                } else {
+                    None
+                };
+                let takes_value = value.is_some();
+                if let Some(command) = apply(&mut config, opt, &option, value)? {
+                    return Ok(command);
+                }
+                if takes_value {
+                    break;
+                }
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    if config.patterns.is_empty() {
+        config
+            .patterns
+            .push(positional.next().ok_or(ArgsError::MissingPattern)?);
+    }
+    config.files.extend(positional);
+    if config.files.is_empty() {
+        let file = if config.recursive { "." } else { "-" };
+        config.files.push(file.to_string());
+    }
+    check_compatible(&config)?;
+    Ok(Command::Search(Box::new(config)))
+}
+
+/// Applies an option to the configuration, returning the command to run instead of searching
+/// if the option is `--help` or `--version`.
+fn apply(
+    config: &mut Config,
+    opt: Opt,
+    option: &str,
+    value: Option<String>,
+) -> Result<Option<Command>, ArgsError> {
+    let value = value.unwrap_or_default();
+    match opt {
+        Opt::Extended => config.syntax = Syntax::Extended,
+        Opt::Basic => config.syntax = Syntax::Basic,
+        Opt::Perl => config.syntax = Syntax::Perl,
+        Opt::FixedStrings => config.fixed_strings = true,
+        Opt::Regexp => config.patterns.push(value),
+        Opt::IgnoreCase => config.case_insensitive = true,
+        Opt::Multiline => config.multiline = true,
+        Opt::InvertMatch => config.invert_match = true,
+        Opt::WithFilename => config.with_filename = Some(true),
+        Opt::NoFilename => config.with_filename = Some(false),
+        Opt::Label => config.label = Some(value),
+        Opt::Count => config.report = Report::Count,
+        Opt::FilesWithMatches => config.report = Report::FilesWithMatches,
+        Opt::FilesWithoutMatch => config.report = Report::FilesWithoutMatch,
+        Opt::LineNumber => config.line_number = true,
+        Opt::ByteOffset => config.byte_offset = true,
+        Opt::Column => config.column = true,
+        Opt::OnlyMatching => config.only_matching = true,
+        Opt::Vimgrep => config.vimgrep = true,
+        Opt::BeforeContext => config.before_context = parse_number(option, &value)?,
+        Opt::AfterContext => config.after_context = parse_number(option, &value)?,
+        Opt::Context => {
+            let lines = parse_number(option, &value)?;
+            config.before_context = lines;
+            config.after_context = lines;
+        }
+        Opt::Recursive => {
+            config.recursive = true;
+            config.follow_links = false;
+        }
+        Opt::DereferenceRecursive => {
+            config.recursive = true;
+            config.follow_links = true;
+        }
+        Opt::MaxDepth => config.max_depth = Some(parse_number(option, &value)?),
+        Opt::Include => config.include.push(parse_glob(option, &value)?),
+        Opt::Exclude => config.exclude.push(parse_glob(option, &value)?),
+        Opt::ExcludeDir => config.exclude_dir.push(parse_glob(option, &value)?),
+        Opt::NoIgnore => config.no_ignore = true,
+        Opt::Hidden => config.hidden = true,
+        Opt::Threads => {
+            config.threads = match parse_number(option, &value)? {
+                0 => return Err(invalid_value(option, &value)),
+                threads => Some(threads),
+            }
+        }
+        Opt::MaxErrors => config.max_errors = Some(parse_number(option, &value)?),
+        Opt::MatchLimit => {
+            config.match_limit = match parse_number(option, &value)? {
+                0 => None,
+                limit => Some(limit),
+            }
+        }
+        Opt::Replace => config.replacement = Some(value),
+        Opt::Color => {
+            config.color = match value.as_str() {
+                "auto" => ColorChoice::Auto,
+                "always" => ColorChoice::Always,
+                "never" => ColorChoice::Never,
+                _ => return Err(invalid_value(option, &value)),
+            }
+        }
+        Opt::Help => return Ok(Some(Command::Help)),
+        Opt::Version => return Ok(Some(Command::Version)),
+    }
+    Ok(None)
+}
+
+fn parse_number(option: &str, value: &str) -> Result<usize, ArgsError> {
+    value.parse().map_err(|_| invalid_value(option, value))
+}
+
+fn invalid_value(option: &str, value: &str) -> ArgsError {
+    ArgsError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Compiles the glob of the option, a trailing `/` is ignored so `--exclude-dir=target/` works too.
+fn parse_glob(option: &str, value: &str) -> Result<Glob, ArgsError> {
+    let pattern = match value.strip_suffix('/') {
+        Some(pattern) if !pattern.is_empty() => pattern,
+        _ => value,
+    };
+    Glob::new(pattern).map_err(|_| invalid_value(option, value))
+}
+
+/// Checks that the options given can be used together.
+fn check_compatible(config: &Config) -> Result<(), ArgsError> {
+    let incompatible = |first: &str, second: &str| {
+        Err(ArgsError::IncompatibleOptions(
+            first.to_string(),
+            second.to_string(),
+        ))
+    };
+    match config {
+        Config {
+            multiline: true,
+            replacement: Some(_),
+            ..
+        } => incompatible("--multiline", "--replace"),
+        Config {
+            max_errors: Some(_),
+            multiline: true,
+            ..
+        } => incompatible("--max-errors", "--multiline"),
+        Config {
+            max_errors: Some(_),
+            replacement: Some(_),
+            ..
+        } => incompatible("--max-errors", "--replace"),
+        Config {
+            invert_match: true,
+            multiline: true,
+            ..
+        } => incompatible("--invert-match", "--multiline"),
+        Config {
+            multiline: true,
+            before_context,
+            after_context,
+            ..
+        } if before_context + after_context > 0 => incompatible("--context", "--multiline"),
+        _ => Ok(()),
+    }
+}
+
+impl Config {
+    /// Returns `true` if the printed lines are prefixed with the name of their file, always with `--vimgrep`.
+    pub fn prints_filenames(&self) -> bool {
+        self.vimgrep
+            || self
+                .with_filename
+                .unwrap_or(self.files.len() > 1 || self.recursive)
+    }
+
+    /// Returns how many files are searched at once.
+    pub fn thread_count(&self) -> usize {
+        self.threads.unwrap_or_else(parallel::default_threads)
+    }
+
+    /// Returns the walker that finds the files to search under the directories.
+    pub fn walker(&self) -> Walker {
+        let mut walker = Walker::new();
+        walker
+            .follow_links(self.follow_links)
+            .max_depth(self.max_depth)
+            .ignore_files(!self.no_ignore)
+            .hidden(self.hidden)
+            .include(self.include.clone())
+            .exclude(self.exclude.clone())
+            .exclude_dir(self.exclude_dir.clone());
+        walker
+    }
+
+    /// Returns the options the patterns are parsed with.
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            case_insensitive: self.case_insensitive,
+            multiline: self.multiline,
+            dot_matches_newline: !self.multiline,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// Builds the matcher of the patterns.
+    /// The approximate matches are only searched by the regex engine, so when errors are allowed,
+    /// the fixed strings are searched as regexes too, with every character escaped.
+    pub fn matcher(&self) -> Result<Matcher, RegexError> {
+        let options = self.parse_options();
+        let mut matcher = match (self.fixed_strings, self.max_errors) {
+            (true, None) => {
+                Matcher::fixed_strings_with_options(&self.patterns, self.case_insensitive)
+            }
+            (true, Some(_)) => {
+                let expression = self
+                    .patterns
+                    .iter()
+                    .flat_map(|pattern| pattern.split('\n'))
+                    .map(escape)
+                    .collect::<Vec<String>>()
+                    .join("|");
+                Matcher::Regex(Regex::with_options(&expression, Syntax::Extended, options)?)
+            }
+            (false, None) => Matcher::with_options(&self.patterns, self.syntax, options)?,
+            (false, Some(_)) => {
+                let regexes = self
+                    .patterns
+                    .iter()
+                    .map(|pattern| Regex::with_options(pattern, self.syntax, options))
+                    .collect::<Result<Vec<Regex>, RegexError>>()?;
+                Matcher::Regex(Regex::union(regexes, options.size_limit)?)
+            }
+        };
+        matcher.set_match_limit(self.match_limit);
+        Ok(matcher)
+    }
+}
+
+/// Escapes every character of the literal, so it is matched as is when parsed as an extended regex.
+fn escape(literal: &str) -> String {
+    literal.chars().flat_map(|c| ['\\', c]).collect()
+}
@@ -5,6 +5,10 @@ use std::fmt;
 pub enum RegexError {
     /// The expression contains an unmatched bracket
     UnmatchedBracket,
+    /// The expression contains an unmatched parenthesis
+    UnmatchedParenthesis,
+    /// The expression contains an invalid or repeated group name
+    InvalidGroupName,
     /// The expression contains an invalid character class name
     InvalidCharacterClassName,
     /// The expression is invalid
@@ -15,17 +19,33 @@ pub enum RegexError {
     InvalidBracketRange,
     /// The file is invalid
     InvalidFile,
+    /// The backtracking engine ran out of steps before finishing the match
+    MatchLimitExceeded,
+    /// The compiled expression would be bigger than the size limit
+    SizeLimitExceeded,
+    /// The groups of the expression are nested deeper than the nesting limit
+    NestingLimitExceeded,
+    /// A match in progress of a multiline search is longer than the buffer limit
+    BufferLimitExceeded,
 }
 
 impl fmt::Display for RegexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::UnmatchedBracket => write!(f, "Unmatched [, [^, [:, [., or [="),
+            Self::UnmatchedParenthesis => write!(f, "Unmatched ( or \\( or ) or \\)"),
+            Self::InvalidGroupName => write!(f, "Invalid group name"),
             Self::InvalidCharacterClassName => write!(f, "Invalid character class name"),
             Self::InvalidRegularExpression => write!(f, "Invalid regular expression"),
             Self::NonAsciiInput => write!(f, "Input is not ASCII"),
             Self::InvalidFile => write!(f, "Invalid file"),
             Self::InvalidBracketRange => write!(f, "Invalid range end"),
+            Self::MatchLimitExceeded => write!(f, "Match limit exceeded"),
+            Self::SizeLimitExceeded => {
+                write!(f, "Compiled regular expression exceeds the size limit")
+            }
+            Self::NestingLimitExceeded => write!(f, "Groups are nested too deeply"),
+            Self::BufferLimitExceeded => write!(f, "Match in progress exceeds the buffer limit"),
         }
     }
 }
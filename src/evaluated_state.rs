@@ -1,11 +1,13 @@
-use crate::regex_state::RegexState;
-
-/// An EvaluatedStep represents an state that has been already evaluated in the process of matching.
-/// It contains the state itself, the size of the match, and a flag indicating if the state is backtrackable.
-/// Backtrackable states are those that can be revisited in the future, cause they can be part of a valid match.
+/// An EvaluatedStep represents a point of the matching process that can be revisited when backtracking.
+/// Every time the matching has to choose between two ways of continuing, it takes the preferred one
+/// and saves the other as an EvaluatedStep, which is resumed if the preferred way fails to match.
 #[derive(Debug)]
-pub struct EvaluatedStep {
-    pub state: RegexState,
-    pub match_size: usize,
-    pub backtrackable: bool,
+pub enum EvaluatedStep {
+    /// Continues matching with the instruction `pc` at the given position of the value.
+    Resume { pc: usize, position: usize },
+    /// Restores the previous content of a slot, undoing a `Mark` of the abandoned way.
+    Restore {
+        slot: usize,
+        position: Option<usize>,
+    },
 }
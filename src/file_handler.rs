@@ -1,13 +1,115 @@
+use crate::cli::Config;
 use crate::error::RegexError;
 use crate::match_result::MatchResult;
-use crate::regex::Regex;
+use crate::matcher::Matcher;
+use crate::replacer;
+use crate::stream::DEFAULT_BUFFER_LIMIT;
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
 use std::path::Path;
 
+/// Approximate size of the blocks of lines read from the file at once.
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// The name shown for the standard input when no `--label` is given.
+pub const STDIN_LABEL: &str = "(standard input)";
+
+/// What is printed for every searched file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Report {
+    /// The selected lines: the ones with a match, or without one when inverting the match.
+    #[default]
+    Lines,
+    /// The number of selected lines.
+    Count,
+    /// The name of the file if it has a selected line, which stops the search of the file.
+    FilesWithMatches,
+    /// The name of the file if it has no selected line.
+    FilesWithoutMatch,
+}
+
+/// When the output is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// The matches are highlighted, and the file names and numbers of the prefixes are colored
+    /// only if the standard output is a terminal.
+    #[default]
+    Auto,
+    /// The matches, the file names and the numbers are always colored.
+    Always,
+    /// Nothing is colored.
+    Never,
+}
+
+impl Report {
+    /// Returns `true` if the first selected line is enough to know what to print, so the rest of the file isn't read.
+    fn stops_at_first(self) -> bool {
+        matches!(self, Report::FilesWithMatches | Report::FilesWithoutMatch)
+    }
+}
+
 /// Module that handles the file reading and processing.
+/// The content can come from any source that implements `Read`, like a file or the standard input.
 pub struct FileHandler {
-    file: File,
+    source: Box<dyn Read + Send>,
+    file_name: String,
+    /// Whether the printed lines are prefixed with the name of the file.
+    with_filename: bool,
+    /// Whether the matches are highlighted, and whether the file names and numbers of the prefixes are colored.
+    color: bool,
+    color_prefix: bool,
+    replacement: Option<String>,
+    max_errors: Option<usize>,
+    multiline: bool,
+    /// The number of bytes a multiline search keeps in memory for a match in progress and its lines.
+    buffer_limit: usize,
+    /// Whether the lines that don't match are printed instead of the ones that do.
+    invert_match: bool,
+    report: Report,
+    /// Whether the prefix of the printed lines has their number, the column of their first match,
+    /// and their offset in the file, see `Position`.
+    line_number: bool,
+    column: bool,
+    byte_offset: bool,
+    /// Whether every match is printed alone on its own line, instead of the whole line.
+    only_matching: bool,
+    /// Whether the whole line is printed once for every match, with the column of the match.
+    vimgrep: bool,
+    context: Context,
+    output: Output,
+}
+
+/// Where a printed line, or a match printed alone, is in the file, shown in its prefix when asked for.
+#[derive(Debug, Clone, Copy, Default)]
+struct Position {
+    line_number: usize,
+    /// The offset of the line, or of the match printed alone, from the start of the file.
+    offset: usize,
+    /// The offset in the line of its first match, or of the match printed alone.
+    column: usize,
+    /// Whether the line is printed as context of a selected one, so its prefix ends with `-` instead of `:`.
+    context: bool,
+}
+
+/// The lines printed around the selected ones, and what is needed to print them while reading the file.
+#[derive(Default)]
+struct Context {
+    before: usize,
+    after: usize,
+    /// The last lines that weren't printed, which are printed if a selected line follows them.
+    previous: VecDeque<(Vec<u8>, Position)>,
+    /// How many of the next lines are still printed after the last selected one.
+    after_left: usize,
+    /// The number of the last printed line, to print `--` before a line that doesn't follow it.
+    last_printed: Option<usize>,
+}
+
+/// Where the printed lines are written.
+enum Output {
+    Stdout,
+    /// The lines are kept in memory until they are taken, see `FileHandler::take_output`.
+    Buffer(Vec<u8>),
 }
 
 impl FileHandler {
@@ -15,49 +117,698 @@ impl FileHandler {
     pub fn new(file_name: &str) -> io::Result<FileHandler> {
         let path = Path::new(file_name);
         let file = File::open(path)?;
-        Ok(FileHandler { file })
+        Ok(FileHandler::from_reader(file, file_name))
+    }
+
+    /// Creates a new FileHandler that reads the content from the source, shown with the given name.
+    pub fn from_reader<R: Read + Send + 'static>(source: R, name: &str) -> FileHandler {
+        FileHandler {
+            source: Box::new(source),
+            file_name: name.to_string(),
+            with_filename: false,
+            color: true,
+            color_prefix: false,
+            replacement: None,
+            max_errors: None,
+            multiline: false,
+            buffer_limit: DEFAULT_BUFFER_LIMIT,
+            invert_match: false,
+            report: Report::Lines,
+            line_number: false,
+            column: false,
+            byte_offset: false,
+            only_matching: false,
+            vimgrep: false,
+            context: Context::default(),
+            output: Output::Stdout,
+        }
+    }
+
+    /// Creates a new FileHandler with the file path, which searches and prints the lines as the configuration asks.
+    /// The file `-` is the standard input, shown with the label of the configuration.
+    /// A directory can't be searched as a file, it has to be walked, see `Config::walker`.
+    pub fn with_config<P: AsRef<Path>>(path: P, config: &Config) -> io::Result<FileHandler> {
+        let path = path.as_ref();
+        let mut handler = if path == Path::new("-") {
+            let label = config.label.as_deref().unwrap_or(STDIN_LABEL);
+            FileHandler::from_reader(io::stdin(), label)
+        } else {
+            let file = File::open(path)?;
+            if file.metadata()?.is_dir() {
+                return Err(io::Error::other("Is a directory"));
+            }
+            FileHandler::from_reader(file, &path.to_string_lossy())
+        };
+        // The vimgrep lines are read by editors, which don't expect any color.
+        (handler.color, handler.color_prefix) = match config.color {
+            _ if config.vimgrep => (false, false),
+            ColorChoice::Auto => (true, io::stdout().is_terminal()),
+            ColorChoice::Always => (true, true),
+            ColorChoice::Never => (false, false),
+        };
+        handler.replacement = config.replacement.clone();
+        handler.max_errors = config.max_errors;
+        handler.multiline = config.multiline;
+        handler.invert_match = config.invert_match;
+        handler.report = config.report;
+        handler.line_number = config.line_number || config.vimgrep;
+        handler.column = config.column || config.vimgrep;
+        handler.byte_offset = config.byte_offset;
+        handler.only_matching = config.only_matching;
+        handler.vimgrep = config.vimgrep;
+        handler.context.before = config.before_context;
+        handler.context.after = config.after_context;
+        handler.with_filename = config.prints_filenames();
+        Ok(handler)
+    }
+
+    /// Sets a template to rewrite the matches of the printed lines, see `replacer::expand` for its syntax.
+    pub fn with_replacement(mut self, template: &str) -> FileHandler {
+        self.replacement = Some(template.to_string());
+        self
+    }
+
+    /// Searches the lines for approximate matches with at most `max_errors` edits, see `Regex::search_fuzzy`.
+    pub fn with_max_errors(mut self, max_errors: usize) -> FileHandler {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Prints the lines without a match instead of the ones with a match, without highlighting them.
+    pub fn with_invert_match(mut self) -> FileHandler {
+        self.invert_match = true;
+        self
+    }
+
+    /// Sets the number of bytes a multiline search keeps in memory for a match in progress and its lines,
+    /// past which the search of the file stops with a `BufferLimitExceeded` error.
+    pub fn with_buffer_limit(mut self, limit: usize) -> FileHandler {
+        self.buffer_limit = limit;
+        self
+    }
+
+    /// Prints the given number of lines before and after every selected line, see `Config::before_context`.
+    pub fn with_context(mut self, before: usize, after: usize) -> FileHandler {
+        self.context.before = before;
+        self.context.after = after;
+        self
+    }
+
+    /// Sets what is printed for the file, see `Report`.
+    pub fn with_report(mut self, report: Report) -> FileHandler {
+        self.report = report;
+        self
+    }
+
+    /// Keeps the printed lines in memory instead of writing them to the standard output, so the output of a file
+    /// can be printed at once, without mixing it with the output of the files searched by other threads.
+    pub fn buffered(mut self) -> FileHandler {
+        self.output = Output::Buffer(vec![]);
+        self
+    }
+
+    /// Returns the lines printed so far by a buffered handler, leaving its buffer empty.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        match &mut self.output {
+            Output::Stdout => vec![],
+            Output::Buffer(buffer) => std::mem::take(buffer),
+        }
+    }
+
+    /// Searches the file with the given matcher, as a whole if the handler is multiline or else line by line.
+    pub fn process(&mut self, matcher: &Matcher) -> Result<(), RegexError> {
+        if self.multiline {
+            self.process_multiline(matcher)
+        } else {
+            self.process_with_matcher(matcher)
+        }
     }
 
-    /// Reads the file line by line and processes each line with the given expression.
-    pub fn process_file(&self, expression: &str) -> Result<(), RegexError> {
-        let regex = match Regex::new(expression) {
-            Ok(regex) => regex,
-            Err(err) => return Err(err),
+    /// Reads the file in blocks of lines and processes each line with the given expression.
+    /// Blocks that don't contain the literals required by the expression are skipped without running the regex.
+    pub fn process_file(&mut self, expression: &str) -> Result<(), RegexError> {
+        let matcher = Matcher::new([expression])?;
+        self.process_with_matcher(&matcher)
+    }
+
+    /// Reads the file in blocks of lines and processes each line with the given matcher.
+    /// An approximate match doesn't need to contain the required literals, so no block is skipped when searching them,
+    /// and neither when inverting the match, since every line of a skipped block would be printed,
+    /// nor when printing context, since the lines of a skipped block can be the context of a selected one.
+    pub fn process_with_matcher(&mut self, matcher: &Matcher) -> Result<(), RegexError> {
+        let mut reader = BufReader::with_capacity(BLOCK_SIZE, self.take_source());
+        let mut block = Vec::with_capacity(BLOCK_SIZE);
+        let mut line_number = 0;
+        let mut block_offset = 0;
+        let mut selected = 0;
+        let skips_blocks =
+            self.max_errors.is_none() && !self.invert_match && !self.prints_context();
+        'blocks: while read_block(&mut reader, &mut block).map_err(|_| RegexError::InvalidFile)? {
+            if skips_blocks && !matcher.is_candidate(&block) {
+                line_number += block.iter().filter(|&&b| b == b'\n').count();
+                block_offset += block.len();
+                continue;
+            }
+            let content = block.strip_suffix(b"\n").unwrap_or(&block);
+            let mut offset = block_offset;
+            for line in content.split(|&b| b == b'\n') {
+                line_number += 1;
+                let position = Position {
+                    line_number,
+                    offset,
+                    ..Position::default()
+                };
+                offset += line.len() + 1;
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if self.process_line(matcher, line, position)? {
+                    selected += 1;
+                    if self.report.stops_at_first() {
+                        break 'blocks;
+                    }
+                } else if self.prints_context() {
+                    self.pass_line(line, position);
+                }
+            }
+            block_offset += block.len();
+        }
+        self.print_report(selected);
+        Ok(())
+    }
+
+    /// Searches the whole file with the given matcher instead of line by line, so a match can span several lines.
+    /// Every line touched by a match is selected once, and printed with the matches highlighted,
+    /// or every match is printed alone with `-o`, or with the first of its lines with `--vimgrep`.
+    /// The file is read in chunks, and only the lines where a match can still start are kept in memory,
+    /// up to the buffer limit.
+    /// The literal patterns can't contain a newline, so they are searched line by line.
+    pub fn process_multiline(&mut self, matcher: &Matcher) -> Result<(), RegexError> {
+        let Matcher::Regex(regex) = matcher else {
+            return self.process_with_matcher(matcher);
         };
-        let reader = BufReader::new(&self.file);
-        for line_result in reader.lines() {
-            match line_result {
-                Ok(line) => match self.process_line(&regex, &line) {
-                    Ok(_) => (),
-                    Err(err) => return Err(err),
-                },
-                Err(_) => return Err(RegexError::InvalidFile),
+        let mut matches = regex
+            .stream_find_iter(self.take_source())
+            .whole_lines()
+            .buffer_limit(self.buffer_limit);
+        let mut group: Option<LineGroup> = None;
+        let mut selected = 0;
+        while let Some(found) = matches.next() {
+            let found = found.map_err(stream_error)?;
+            let line_number = matches.line_number(found.start);
+            let (lines_start, lines) = matches.matched_lines(&found).map_err(stream_error)?;
+            match &mut group {
+                Some(group) if lines_start <= group.end() => {
+                    group.extend(lines_start, lines, found.range())
+                }
+                _ => {
+                    let next = LineGroup::new(line_number, lines_start, lines, found.range());
+                    if let Some(group) = group.replace(next) {
+                        selected += self.select_group(&group);
+                    }
+                }
             }
+            if self.report.stops_at_first() {
+                break;
+            }
+        }
+        if let Some(group) = group {
+            selected += self.select_group(&group);
         }
+        self.print_report(selected);
         Ok(())
     }
 
-    /// Processes a single line with the given expression. Prints the line if there's a match.
-    fn process_line(&self, regex: &Regex, line: &str) -> Result<(), RegexError> {
-        let result = regex.match_expression(line);
-        match result {
-            Ok(Some(match_result)) => {
-                self.print_with_color(line, &match_result);
-                Ok(())
+    /// Returns `true` if lines are printed around the selected ones. They aren't when only the matches are printed,
+    /// nor in a multiline search, which doesn't keep the lines without a match.
+    fn prints_context(&self) -> bool {
+        self.context.before + self.context.after > 0
+            && self.report == Report::Lines
+            && !self.only_matching
+            && !self.vimgrep
+            && !self.multiline
+    }
+
+    /// Handles a line that isn't selected: it is printed if it closely follows a selected line,
+    /// or else kept in case a selected line closely follows it.
+    fn pass_line(&mut self, line: &[u8], position: Position) {
+        let position = Position {
+            context: true,
+            ..position
+        };
+        if self.context.after_left > 0 {
+            self.context.after_left -= 1;
+            self.print_line(line, position);
+        } else if self.context.before > 0 {
+            if self.context.previous.len() == self.context.before {
+                self.context.previous.pop_front();
+            }
+            self.context.previous.push_back((line.to_vec(), position));
+        }
+    }
+
+    /// Prints the lines kept before a selected line, which is printed next, and starts counting the lines after it.
+    fn print_previous(&mut self) {
+        for (line, position) in std::mem::take(&mut self.context.previous) {
+            self.print_line(&line, position);
+        }
+        self.context.after_left = self.context.after;
+    }
+
+    /// Takes the source out of the handler to read it, leaving an empty one in its place,
+    /// so the content is only read once, like a file whose end was reached.
+    fn take_source(&mut self) -> Box<dyn Read + Send> {
+        std::mem::replace(&mut self.source, Box::new(io::empty()))
+    }
+
+    /// Prints the lines of the group, or its matches, unless only a report of the file is printed,
+    /// returning how many lines there are.
+    fn select_group(&mut self, group: &LineGroup) -> usize {
+        match self.report {
+            Report::Lines if self.only_matching || self.vimgrep => self.print_group_matches(group),
+            Report::Lines => self.print_group(group),
+            _ => {}
+        }
+        group.lines.split(|&b| b == b'\n').count()
+    }
+
+    /// Prints what the report asks for once the file was searched, given the number of selected lines.
+    /// The counts are prefixed with the file name like the lines, and the file names are printed alone,
+    /// both without colors whatever `--color` says, since they are usually read by other programs.
+    fn print_report(&mut self, selected: usize) {
+        let mut output = vec![];
+        match self.report {
+            Report::Lines => return,
+            Report::Count => {
+                if self.with_filename {
+                    output.extend_from_slice(self.file_name.as_bytes());
+                    output.push(b':');
+                }
+                output.extend_from_slice(selected.to_string().as_bytes());
+            }
+            Report::FilesWithMatches if selected > 0 => {
+                output.extend_from_slice(self.file_name.as_bytes())
+            }
+            Report::FilesWithoutMatch if selected == 0 => {
+                output.extend_from_slice(self.file_name.as_bytes())
             }
-            Ok(None) => Ok(()),
-            Err(err) => Err(err),
+            Report::FilesWithMatches | Report::FilesWithoutMatch => return,
         }
+        output.push(b'\n');
+        self.emit(&output);
     }
 
+    /// Prints the lines of the group, highlighting the part of every match that falls inside each line.
+    fn print_group(&mut self, group: &LineGroup) {
+        let mut output = Vec::with_capacity(group.lines.len() + 10);
+        let mut line_start = group.start;
+        for (i, line) in group.lines.split(|&b| b == b'\n').enumerate() {
+            let line_end = line_start + line.len();
+            let mut last = 0;
+            let first_match = group
+                .spans
+                .iter()
+                .find(|&&(start, end)| end > line_start || start >= line_start)
+                .map_or(line_start, |&(start, _)| start);
+            let position = Position {
+                line_number: group.line_number + i,
+                offset: line_start,
+                column: first_match.saturating_sub(line_start),
+                ..Position::default()
+            };
+            self.write_prefix(&mut output, Some(position));
+            if self.color {
+                for &(start, end) in &group.spans {
+                    let start = start.clamp(line_start, line_end) - line_start;
+                    let end = end.clamp(line_start, line_end) - line_start;
+                    if start == end {
+                        continue;
+                    }
+                    output.extend_from_slice(&line[last..start]);
+                    output.extend_from_slice(b"\x1b[31m");
+                    output.extend_from_slice(&line[start..end]);
+                    output.extend_from_slice(b"\x1b[0m");
+                    last = end;
+                }
+            }
+            output.extend_from_slice(&line[last..]);
+            output.push(b'\n');
+            line_start = line_end + 1;
+        }
+        self.emit(&output);
+    }
+
+    /// Prints every non-empty match of the group alone, or the first line it touches with `--vimgrep`,
+    /// with the position of its start in the prefix. A match that ends with a newline is printed without it.
+    fn print_group_matches(&mut self, group: &LineGroup) {
+        for &(start, end) in group.spans.iter().filter(|(start, end)| end > start) {
+            let end = end.min(group.end());
+            let line_start = group.line_start(start);
+            let line_end = group.line_end(start);
+            let position = Position {
+                line_number: group.line_number_at(start),
+                offset: start,
+                column: start - line_start,
+                ..Position::default()
+            };
+            if self.vimgrep {
+                let line = &group.lines[line_start - group.start..line_end - group.start];
+                let position = Position {
+                    offset: line_start,
+                    ..position
+                };
+                self.print_with_color(
+                    line,
+                    start - line_start,
+                    end.min(line_end) - line_start,
+                    position,
+                );
+            } else {
+                let matched = &group.lines[start - group.start..end - group.start];
+                self.print_with_color(matched, 0, matched.len(), position);
+            }
+        }
+    }
+
+    /// Processes a single line with the given expression, returning `true` if the line is selected:
+    /// if it has a match, or doesn't when inverting the match. The selected lines are printed unless only
+    /// a report of the file is printed.
+    /// The line doesn't need to be valid UTF-8, it is printed with its original bytes.
+    /// If the backtracking engine runs out of steps, a warning is printed and the line is searched again
+    /// with the linear engine.
+    fn process_line(
+        &mut self,
+        matcher: &Matcher,
+        line: &[u8],
+        position: Position,
+    ) -> Result<bool, RegexError> {
+        let found = match self.max_errors {
+            Some(max_errors) => Ok(matcher.find_fuzzy(line, max_errors)),
+            None => matcher.find_bytes(line),
+        };
+        let found = match found {
+            Err(RegexError::MatchLimitExceeded) => {
+                eprintln!(
+                    "grep: warning: match limit exceeded on line {}, searching it with the linear engine",
+                    position.line_number
+                );
+                matcher.find_bytes_linear(line)
+            }
+            result => result?,
+        };
+        if found.is_some() == self.invert_match {
+            return Ok(false);
+        }
+        if self.report != Report::Lines {
+            return Ok(true);
+        }
+        if self.prints_context() {
+            self.print_previous();
+        }
+        let Some((start, end)) = found else {
+            // An inverted line has no match to print alone.
+            if !self.only_matching {
+                self.print_line(line, position);
+            }
+            return Ok(true);
+        };
+        if self.only_matching || self.vimgrep {
+            self.print_matches(matcher, line, (start, end), position);
+            return Ok(true);
+        }
+        let position = Position {
+            column: start,
+            ..position
+        };
+        match &self.replacement {
+            Some(template) => {
+                let replaced = self.replace(matcher, &String::from_utf8_lossy(line), template);
+                self.print_line(replaced.as_bytes(), position);
+            }
+            None => self.print_with_color(line, start, end, position),
+        }
+        Ok(true)
+    }
+
+    /// Prints every match of the line alone, or the whole line once for every match with `--vimgrep`,
+    /// with the position of the match in the prefix.
+    /// The matches are searched again in the line with its invalid UTF-8 replaced, like when replacing them,
+    /// and their offsets mapped back to the original bytes, which are printed, except for the replacements.
+    /// The approximate match is the only one printed.
+    fn print_matches(
+        &mut self,
+        matcher: &Matcher,
+        line: &[u8],
+        found: (usize, usize),
+        position: Position,
+    ) {
+        let text = String::from_utf8_lossy(line);
+        let matches: Vec<(usize, usize, MatchResult)> = match self.max_errors {
+            Some(_) => {
+                let (start, end) = found;
+                let matched = String::from_utf8_lossy(&line[start..end]).into_owned();
+                vec![(start, end, MatchResult::new(start, end, matched))]
+            }
+            None => matcher
+                .find_iter(&text)
+                .filter(|match_result| match_result.end > match_result.start)
+                .map(|match_result| {
+                    let start = original_offset(line, match_result.start);
+                    let end = original_offset(line, match_result.end);
+                    (start, end, match_result)
+                })
+                .collect(),
+        };
+        for (start, end, match_result) in matches {
+            if self.vimgrep {
+                let position = Position {
+                    column: start,
+                    ..position
+                };
+                self.print_with_color(line, start, end, position);
+                continue;
+            }
+            let position = Position {
+                offset: position.offset + start,
+                column: start,
+                ..position
+            };
+            match &self.replacement {
+                Some(template) => {
+                    let mut replaced = String::new();
+                    replacer::expand(template, &match_result, &mut replaced);
+                    self.print_with_color(replaced.as_bytes(), 0, replaced.len(), position);
+                }
+                None => self.print_with_color(&line[start..end], 0, end - start, position),
+            }
+        }
+    }
+
+    fn print_with_color(&mut self, line: &[u8], start: usize, end: usize, position: Position) {
+        let mut output = Vec::with_capacity(line.len() + 10);
+        self.write_prefix(&mut output, Some(position));
+        if self.color {
+            output.extend_from_slice(&line[..start]);
+            output.extend_from_slice(b"\x1b[31m");
+            output.extend_from_slice(&line[start..end]);
+            output.extend_from_slice(b"\x1b[0m");
+            output.extend_from_slice(&line[end..]);
+        } else {
+            output.extend_from_slice(line);
+        }
+        output.push(b'\n');
+        self.emit(&output);
+    }
+
+    /// Returns the line with every match replaced by the expansion of the template.
+    /// The bytes of the line that aren't valid UTF-8 are printed as replacement characters.
+    fn replace(&self, matcher: &Matcher, line: &str, template: &str) -> String {
+        let mut replaced = String::with_capacity(line.len());
+        let mut last = 0;
+        for match_result in matcher.find_iter(line) {
+            replaced.push_str(&line[last..match_result.start]);
+            if self.color {
+                replaced.push_str("\x1b[31m");
+            }
+            replacer::expand(template, &match_result, &mut replaced);
+            if self.color {
+                replaced.push_str("\x1b[0m");
+            }
+            last = match_result.end;
+        }
+        replaced.push_str(&line[last..]);
+        replaced
+    }
+
+    /// Prints the line as is, after its prefix.
+    fn print_line(&mut self, line: &[u8], position: Position) {
+        let mut output = Vec::with_capacity(line.len() + 10);
+        self.write_prefix(&mut output, Some(position));
+        output.extend_from_slice(line);
+        output.push(b'\n');
+        self.emit(&output);
+    }
+
+    /// Writes the prefix of a printed line: the name of the file, if the lines are prefixed with it,
+    /// and the line number, the column and the offset of the position, the ones that were asked for.
+    /// The column is 1-based and counted in bytes, like the offset. A context line has no column,
+    /// its numbers are followed by `-`, and it is preceded by `--`, like a selected line, if it doesn't follow
+    /// the last printed line.
+    fn write_prefix(&mut self, output: &mut Vec<u8>, position: Option<Position>) {
+        let separator = match position {
+            Some(position) if position.context => b'-',
+            _ => b':',
+        };
+        if let Some(position) = position.filter(|_| self.prints_context()) {
+            let last_printed = self.context.last_printed.replace(position.line_number);
+            if last_printed.is_some_and(|last| position.line_number > last + 1) {
+                output.extend_from_slice(b"--\n");
+            }
+        }
+        if self.with_filename {
+            self.write_file_name(output);
+            output.push(separator);
+        }
+        let Some(position) = position else {
+            return;
+        };
+        let numbers = [
+            (self.line_number, position.line_number),
+            (self.column && !position.context, position.column + 1),
+            (self.byte_offset, position.offset),
+        ];
+        for (_, number) in numbers.into_iter().filter(|(shown, _)| *shown) {
+            if self.color_prefix {
+                output.extend_from_slice(b"\x1b[32m");
+                output.extend_from_slice(number.to_string().as_bytes());
+                output.extend_from_slice(b"\x1b[0m");
+            } else {
+                output.extend_from_slice(number.to_string().as_bytes());
+            }
+            output.push(separator);
+        }
+    }
+
+    /// Writes the name of the file, colored if the prefixes are.
+    fn write_file_name(&self, output: &mut Vec<u8>) {
+        if self.color_prefix {
+            output.extend_from_slice(b"\x1b[35m");
+            output.extend_from_slice(self.file_name.as_bytes());
+            output.extend_from_slice(b"\x1b[0m");
+        } else {
+            output.extend_from_slice(self.file_name.as_bytes());
+        }
+    }
+
+    /// Writes the printed lines to the output of the handler.
+    fn emit(&mut self, output: &[u8]) {
+        match &mut self.output {
+            Output::Stdout => {
+                let _ = io::stdout().lock().write_all(output);
+            }
+            Output::Buffer(buffer) => buffer.extend_from_slice(output),
+        }
+    }
+}
+
+/// The consecutive lines touched by one or more matches of a multiline search, printed together.
+struct LineGroup {
+    /// The 1-based number of the first line.
+    line_number: usize,
+    /// The offset of the first line in the file.
+    start: usize,
+    /// The content of the lines, without the newline at the end of the last one.
+    lines: Vec<u8>,
+    /// The offsets in the file of the matches in the lines.
+    spans: Vec<(usize, usize)>,
+}
+
+impl LineGroup {
+    fn new(line_number: usize, start: usize, lines: &[u8], span: (usize, usize)) -> LineGroup {
+        LineGroup {
+            line_number,
+            start,
+            lines: lines.to_vec(),
+            spans: vec![span],
+        }
+    }
+
+    /// Returns the offset in the file of the end of the last line.
+    fn end(&self) -> usize {
+        self.start + self.lines.len()
+    }
+
+    /// Returns the number of the line of the group where the given offset is.
+    fn line_number_at(&self, offset: usize) -> usize {
+        let newlines = self.lines[..offset - self.start]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        self.line_number + newlines
+    }
+
+    /// Returns the offset in the file of the start of the line of the group where the given offset is.
+    fn line_start(&self, offset: usize) -> usize {
+        self.lines[..offset - self.start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(self.start, |newline| self.start + newline + 1)
+    }
+
+    /// Returns the offset in the file of the end of the line of the group where the given offset is.
+    fn line_end(&self, offset: usize) -> usize {
+        self.lines[offset - self.start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.end(), |newline| offset + newline)
+    }
+
+    /// Adds a match whose lines start inside the group, appending the lines that aren't in the group yet.
+    fn extend(&mut self, start: usize, lines: &[u8], span: (usize, usize)) {
+        let end = self.end();
+        if start + lines.len() > end {
+            self.lines.extend_from_slice(&lines[end - start..]);
+        }
+        self.spans.push(span);
+    }
+}
+
+/// Returns the offset in the line of the given offset in the line with its invalid UTF-8 replaced,
+/// where every invalid sequence became a single replacement character, like `String::from_utf8_lossy` does.
+fn original_offset(line: &[u8], lossy_offset: usize) -> usize {
+    let mut lossy = 0;
+    let mut original = 0;
+    for chunk in line.utf8_chunks() {
+        let valid = chunk.valid().len();
+        if lossy_offset <= lossy + valid {
+            return original + lossy_offset - lossy;
+        }
+        lossy += valid;
+        original += valid;
+        if !chunk.invalid().is_empty() {
+            if lossy_offset < lossy + char::REPLACEMENT_CHARACTER.len_utf8() {
+                return original;
+            }
+            lossy += char::REPLACEMENT_CHARACTER.len_utf8();
+            original += chunk.invalid().len();
+        }
+    }
+    original
+}
+
+/// Returns the error of a multiline search that couldn't read the file or keep the match in progress in memory.
+fn stream_error(error: io::Error) -> RegexError {
+    match error.kind() {
+        io::ErrorKind::OutOfMemory => RegexError::BufferLimitExceeded,
+        _ => RegexError::InvalidFile,
+    }
+}
 
-    fn print_with_color(&self, line: &str, match_result: &MatchResult) {
-        let (start, end) = match_result.range();
-    println!(
-        "{}\x1b[31m{}\x1b[0m{}",
-        &line[..start],    
-        &line[start..end],
-        &line[end..]
-    );
+/// Reads the next block of complete lines into `block`, replacing its previous content.
+/// Returns `false` once the end of the file is reached.
+fn read_block<R: BufRead>(reader: &mut R, block: &mut Vec<u8>) -> io::Result<bool> {
+    block.clear();
+    reader.by_ref().take(BLOCK_SIZE as u64).read_to_end(block)?;
+    if block.last().is_some_and(|&b| b != b'\n') {
+        reader.read_until(b'\n', block)?;
     }
+    Ok(!block.is_empty())
 }
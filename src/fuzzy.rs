@@ -0,0 +1,328 @@
+use crate::match_result::{Edit, EditKind};
+use crate::nfa::{Inst, Nfa};
+use crate::utf8;
+
+/// The longest literal searched with the bit-parallel algorithm, one bit per character.
+pub const MAX_BIT_PARALLEL_LEN: usize = 64;
+
+/// An approximate match: a part of the value that is a match of the expression after the given edits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub start: usize,
+    pub end: usize,
+    pub edits: Vec<Edit>,
+}
+
+impl FuzzyMatch {
+    /// Returns the number of edits of the match.
+    pub fn cost(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns `true` if this match is preferred over the other one:
+    /// it has less edits, or as many but it starts earlier, or starts at the same position but is longer.
+    fn is_better_than(&self, other: &FuzzyMatch) -> bool {
+        (self.cost(), self.start, other.end) < (other.cost(), other.start, self.end)
+    }
+}
+
+/// A thread of the approximate simulation: where its match started and the edits it took so far.
+#[derive(Debug, Clone)]
+struct Thread {
+    start: usize,
+    edits: Vec<Edit>,
+}
+
+impl Thread {
+    fn with_edit(&self, kind: EditKind, position: usize) -> Thread {
+        let mut edits = self.edits.clone();
+        edits.push(Edit { kind, position });
+        Thread {
+            start: self.start,
+            edits,
+        }
+    }
+
+    /// Returns `true` if the thread leads to better matches than the other one at the same instruction.
+    fn is_better_than(&self, other: &Thread) -> bool {
+        (self.edits.len(), self.start) < (other.edits.len(), other.start)
+    }
+}
+
+/// Simulates the automaton of a single pattern over the value allowing up to `max_errors` edits,
+/// returning the best approximate match, see `FuzzyMatch::is_better_than`.
+///
+/// Every instruction that consumes a character can also be substituted, deleted, or preceded by an inserted
+/// character, each of them costing one error. At every position, only the cheapest thread that started earliest
+/// is kept for each instruction, so the simulation takes linear time in the length of the value.
+pub fn search(nfa: &Nfa, value: &[u8], max_errors: usize) -> Option<FuzzyMatch> {
+    let mut best: Option<FuzzyMatch> = None;
+    let mut current: Vec<Option<Thread>> = vec![None; nfa.insts.len()];
+    let mut seeds: Vec<(usize, Thread)> = vec![];
+    let mut pos = 0;
+    loop {
+        seeds.push((
+            nfa.starts[0],
+            Thread {
+                start: pos,
+                edits: vec![],
+            },
+        ));
+        add_threads(nfa, &mut current, &mut seeds, pos, value, max_errors);
+
+        for (pc, thread) in current.iter().enumerate() {
+            if let (Inst::Match(_), Some(thread)) = (&nfa.insts[pc], thread) {
+                let found = FuzzyMatch {
+                    start: thread.start,
+                    end: pos,
+                    edits: thread.edits.clone(),
+                };
+                if best.as_ref().is_none_or(|best| found.is_better_than(best)) {
+                    best = Some(found);
+                }
+            }
+        }
+        if pos == value.len() {
+            break;
+        }
+
+        let size = utf8::char_len(&value[pos..]);
+        for (pc, thread) in current.iter_mut().enumerate() {
+            let (Inst::Value(value_to_match), Some(thread)) = (&nfa.insts[pc], thread.take())
+            else {
+                continue;
+            };
+            if value_to_match.matches(&value[pos..]) == size {
+                seeds.push((pc + 1, thread));
+                continue;
+            }
+            if thread.edits.len() < max_errors {
+                seeds.push((pc + 1, thread.with_edit(EditKind::Substitution, pos)));
+                seeds.push((pc, thread.with_edit(EditKind::Insertion, pos)));
+            }
+        }
+        current.fill(None);
+        if best.as_ref().is_some_and(|best| {
+            best.cost() == 0 && seeds.iter().all(|(_, thread)| thread.start > best.start)
+        }) {
+            break;
+        }
+        pos += size;
+    }
+    best
+}
+
+/// Adds the seeded threads to the list, following the instructions that don't consume input,
+/// and the deletions of the ones that do.
+/// A thread replaces the one at the same instruction only if it is better, so the cheapest threads win.
+fn add_threads(
+    nfa: &Nfa,
+    list: &mut [Option<Thread>],
+    stack: &mut Vec<(usize, Thread)>,
+    pos: usize,
+    value: &[u8],
+    max_errors: usize,
+) {
+    while let Some((pc, thread)) = stack.pop() {
+        if list[pc]
+            .as_ref()
+            .is_some_and(|existing| !thread.is_better_than(existing))
+        {
+            continue;
+        }
+        list[pc] = Some(thread.clone());
+        let at_end = pos == value.len();
+        match &nfa.insts[pc] {
+            Inst::Split(first, second) => {
+                stack.push((*second, thread.clone()));
+                stack.push((*first, thread));
+            }
+            Inst::Jump(target) => stack.push((*target, thread)),
+            Inst::AssertStart if pos == 0 => stack.push((pc + 1, thread)),
+            Inst::AssertEnd if at_end => stack.push((pc + 1, thread)),
+            Inst::AssertLineStart if pos == 0 || value[pos - 1] == b'\n' => {
+                stack.push((pc + 1, thread))
+            }
+            Inst::AssertLineEnd if at_end || value[pos] == b'\n' => stack.push((pc + 1, thread)),
+            Inst::Mark(_) | Inst::Progress(_) => stack.push((pc + 1, thread)),
+            Inst::Value(_) if thread.edits.len() < max_errors => {
+                stack.push((pc + 1, thread.with_edit(EditKind::Deletion, pos)));
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Searches a literal allowing up to a number of edits with Myers' bit-parallel algorithm,
+/// which computes the edit distance of the literal to the text ending at every position in a few
+/// word operations per character.
+#[derive(Debug, Clone)]
+pub struct Myers {
+    pattern: Vec<char>,
+    /// The bitmask of the positions of every character in the pattern.
+    masks: Vec<(char, u64)>,
+}
+
+impl Myers {
+    /// Creates the searcher for the literal, or returns `None` if it is empty or longer than `MAX_BIT_PARALLEL_LEN`.
+    pub fn new(literal: &str) -> Option<Myers> {
+        let pattern: Vec<char> = literal.chars().collect();
+        if pattern.is_empty() || pattern.len() > MAX_BIT_PARALLEL_LEN {
+            return None;
+        }
+        let mut masks: Vec<(char, u64)> = vec![];
+        for (i, &c) in pattern.iter().enumerate() {
+            match masks.iter_mut().find(|(other, _)| *other == c) {
+                Some((_, mask)) => *mask |= 1 << i,
+                None => masks.push((c, 1 << i)),
+            }
+        }
+        Some(Myers { pattern, masks })
+    }
+
+    fn mask(&self, c: Option<char>) -> u64 {
+        self.masks
+            .iter()
+            .find(|(other, _)| Some(*other) == c)
+            .map_or(0, |(_, mask)| *mask)
+    }
+
+    /// Returns the best approximate match of the literal in the value with at most `max_errors` edits,
+    /// the same one `search` finds for the automaton of the literal.
+    ///
+    /// The bit-parallel pass finds the lowest cost and the positions where the matches with that cost end.
+    /// The start and the edits of each of them are then found by aligning the literal with the text before it.
+    pub fn find(&self, value: &[u8], max_errors: usize) -> Option<FuzzyMatch> {
+        let chars = decode(value);
+        let last = 1 << (self.pattern.len() - 1);
+        let (mut positive, mut negative) = (u64::MAX, 0u64);
+        let mut score = self.pattern.len();
+        let mut scores = vec![score];
+        for &(_, c) in &chars {
+            let equal = self.mask(c);
+            let vertical = equal | negative;
+            let horizontal = (((equal & positive).wrapping_add(positive)) ^ positive) | equal;
+            let mut horizontal_positive = negative | !(horizontal | positive);
+            let mut horizontal_negative = positive & horizontal;
+            if horizontal_positive & last != 0 {
+                score += 1;
+            } else if horizontal_negative & last != 0 {
+                score -= 1;
+            }
+            horizontal_positive <<= 1;
+            horizontal_negative <<= 1;
+            positive = horizontal_negative | !(vertical | horizontal_positive);
+            negative = horizontal_positive & vertical;
+            scores.push(score);
+        }
+
+        let cost = *scores.iter().min()?;
+        if cost > max_errors {
+            return None;
+        }
+        let mut best: Option<FuzzyMatch> = None;
+        for (end, _) in scores
+            .iter()
+            .enumerate()
+            .filter(|(_, &score)| score == cost)
+        {
+            let found = self.align(&chars, end, cost, value.len());
+            if best.as_ref().is_none_or(|best| found.is_better_than(best)) {
+                best = Some(found);
+            }
+        }
+        best
+    }
+
+    /// Aligns the literal with the text ending after the character `end`, returning the match with `cost` edits
+    /// that starts the earliest.
+    /// `chars` are the positions and the characters of the value, `None` for the bytes that aren't valid UTF-8.
+    fn align(
+        &self,
+        chars: &[(usize, Option<char>)],
+        end: usize,
+        cost: usize,
+        len: usize,
+    ) -> FuzzyMatch {
+        let m = self.pattern.len();
+        // A match with `cost` edits can't be longer than the literal plus `cost` inserted characters.
+        let width = end.min(m + cost);
+        // distances[i][k] is the edit distance between the last `i` characters of the literal
+        // and the last `k` characters of the text before `end`.
+        let mut distances = vec![(0..=width).collect::<Vec<usize>>()];
+        for i in 1..=m {
+            let mut row = vec![i];
+            for k in 1..=width {
+                let same = Some(self.pattern[m - i]) == chars[end - k].1;
+                let replaced = distances[i - 1][k - 1] + usize::from(!same);
+                let deleted = distances[i - 1][k] + 1;
+                let inserted = row[k - 1] + 1;
+                row.push(replaced.min(deleted).min(inserted));
+            }
+            distances.push(row);
+        }
+        let mut k = (0..=width)
+            .rev()
+            .find(|&k| distances[m][k] == cost)
+            .unwrap_or(0);
+        let position = |k: usize| chars.get(end - k).map_or(len, |&(position, _)| position);
+        let start = position(k);
+
+        let mut edits = vec![];
+        let mut i = m;
+        while i > 0 || k > 0 {
+            let distance = distances[i][k];
+            if i > 0 && k > 0 {
+                let same = Some(self.pattern[m - i]) == chars[end - k].1;
+                if distances[i - 1][k - 1] + usize::from(!same) == distance {
+                    if !same {
+                        edits.push(Edit {
+                            kind: EditKind::Substitution,
+                            position: position(k),
+                        });
+                    }
+                    i -= 1;
+                    k -= 1;
+                    continue;
+                }
+            }
+            if i > 0 && distances[i - 1][k] + 1 == distance {
+                edits.push(Edit {
+                    kind: EditKind::Deletion,
+                    position: position(k),
+                });
+                i -= 1;
+            } else {
+                edits.push(Edit {
+                    kind: EditKind::Insertion,
+                    position: position(k),
+                });
+                k -= 1;
+            }
+        }
+        FuzzyMatch {
+            start,
+            end: position(0),
+            edits,
+        }
+    }
+}
+
+/// Returns the position and the character of every character of the value, `None` for the bytes that aren't valid UTF-8.
+fn decode(value: &[u8]) -> Vec<(usize, Option<char>)> {
+    let mut chars = vec![];
+    let mut pos = 0;
+    while pos < value.len() {
+        match utf8::decode_char(&value[pos..]) {
+            Some((c, size)) => {
+                chars.push((pos, Some(c)));
+                pos += size;
+            }
+            None => {
+                chars.push((pos, None));
+                pos += 1;
+            }
+        }
+    }
+    chars
+}
@@ -0,0 +1,245 @@
+use crate::bracket_expression::BracketExpression;
+use crate::error::RegexError;
+use std::path::{Component, Path};
+
+/// A piece of a glob pattern, matched against the characters of a path.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(char),
+    /// `?`, any character but `/`.
+    AnyChar,
+    /// `*`, any sequence of characters without `/`.
+    Star,
+    /// `**/`, any sequence of whole directories, including none.
+    AnyDirs,
+    /// A trailing `/**`'s `**`, any sequence of characters, `/` included.
+    AnyPath,
+    /// `[...]`, any character of the list but `/`, or any character not in the list with `[!...]` or `[^...]`.
+    Bracket(BracketExpression),
+}
+
+/// A shell pattern that selects files by their name or path.
+///
+/// `*` matches any sequence of characters but `/`, `?` any single one, and `[...]` any single one of the list,
+/// with the same ranges as the bracket expressions of the regexes.
+/// `**` as a whole path component matches any number of directories, and `{a,b}` matches any of the
+/// comma separated alternatives, which can be patterns themselves. A `\` makes the next character literal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glob {
+    pattern: String,
+    /// The patterns of every combination of the alternatives between braces.
+    alternatives: Vec<Vec<Token>>,
+}
+
+impl Glob {
+    /// Compiles the pattern, returning an `InvalidBracketRange` error if a range of a bracket ends before it starts.
+    /// A `[` or `{` that isn't closed is a literal character.
+    pub fn new(pattern: &str) -> Result<Glob, RegexError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let alternatives = expand_braces(&chars)
+            .iter()
+            .map(|alternative| tokenize(alternative))
+            .collect::<Result<Vec<Vec<Token>>, RegexError>>()?;
+        Ok(Glob {
+            pattern: pattern.to_string(),
+            alternatives,
+        })
+    }
+
+    /// Returns the pattern the glob was compiled from.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if the whole text matches the pattern.
+    pub fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        self.alternatives
+            .iter()
+            .any(|tokens| matches_tokens(tokens, &text))
+    }
+
+    /// Returns `true` if the path matches the pattern.
+    /// A pattern without `/` is matched against the name of the file, and one with a `/` against the whole
+    /// path, with its components separated by `/`, so relative paths should be relative to the searched directory.
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        if !self.pattern.contains('/') {
+            return path
+                .file_name()
+                .is_some_and(|name| self.matches(&name.to_string_lossy()));
+        }
+        self.matches(&slash_path(path))
+    }
+}
+
+/// Returns the path with its components separated by `/` whatever the platform, without the `.` components.
+pub fn slash_path(path: &Path) -> String {
+    let components: Vec<String> = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    components.join("/")
+}
+
+/// Returns the patterns of every combination of the alternatives between braces, in order.
+/// `a{b,c{d,e}}f` is expanded to `abf`, `acdf` and `acef`.
+fn expand_braces(chars: &[char]) -> Vec<Vec<char>> {
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '[' => i = bracket_end(chars, i).unwrap_or(i) + 1,
+            '{' => {
+                let Some((close, commas)) = brace_end(chars, i) else {
+                    i += 1;
+                    continue;
+                };
+                let bounds: Vec<usize> = std::iter::once(i)
+                    .chain(commas)
+                    .chain(std::iter::once(close))
+                    .collect();
+                return bounds
+                    .windows(2)
+                    .flat_map(|bound| {
+                        let mut expanded = chars[..i].to_vec();
+                        expanded.extend(&chars[bound[0] + 1..bound[1]]);
+                        expanded.extend(&chars[close + 1..]);
+                        expand_braces(&expanded)
+                    })
+                    .collect();
+            }
+            _ => i += 1,
+        }
+    }
+    vec![chars.to_vec()]
+}
+
+/// Returns the position of the `}` closing the brace that opens at `open`, and the positions of the commas
+/// that separate its alternatives, or `None` if it isn't closed.
+fn brace_end(chars: &[char], open: usize) -> Option<(usize, Vec<usize>)> {
+    let mut depth = 0;
+    let mut commas = vec![];
+    let mut i = open + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => i = bracket_end(chars, i).unwrap_or(i),
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some((i, commas)),
+            '}' => depth -= 1,
+            ',' if depth == 0 => commas.push(i),
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns the position of the `]` closing the bracket that opens at `open`, or `None` if it isn't closed.
+/// A `]` right after the `[`, or after the `!` or `^` that negates it, is part of the list.
+fn bracket_end(chars: &[char], open: usize) -> Option<usize> {
+    let mut i = open + 1;
+    if matches!(chars.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    chars[i.min(chars.len())..]
+        .iter()
+        .position(|&c| c == ']')
+        .map(|offset| i + offset)
+}
+
+/// Splits a pattern without braces into its tokens.
+fn tokenize(chars: &[char]) -> Result<Vec<Token>, RegexError> {
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                tokens.push(Token::Literal(chars[i + 1]));
+                i += 2;
+            }
+            '*' => {
+                let stars = chars[i..].iter().take_while(|&&c| c == '*').count();
+                let component_start = i == 0 || chars[i - 1] == '/';
+                let next = chars.get(i + stars);
+                i += stars;
+                match (stars, component_start, next) {
+                    (2.., true, Some('/')) => {
+                        tokens.push(Token::AnyDirs);
+                        i += 1;
+                    }
+                    (2.., true, None) => tokens.push(Token::AnyPath),
+                    _ => tokens.push(Token::Star),
+                }
+            }
+            '?' => {
+                tokens.push(Token::AnyChar);
+                i += 1;
+            }
+            '[' => match bracket_end(chars, i) {
+                Some(close) => {
+                    let mut expression = chars[i + 1..close].to_vec();
+                    if expression.first() == Some(&'!') {
+                        expression[0] = '^';
+                    }
+                    tokens.push(Token::Bracket(BracketExpression::parse(&expression)?));
+                    i = close + 1;
+                }
+                None => {
+                    tokens.push(Token::Literal('['));
+                    i += 1;
+                }
+            },
+            c => {
+                tokens.push(Token::Literal(c));
+                i += 1;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns `true` if the tokens match the whole text.
+/// It keeps the set of positions of the text where the tokens matched so far can end,
+/// so it takes at most quadratic time instead of backtracking.
+fn matches_tokens(tokens: &[Token], text: &[char]) -> bool {
+    let mut positions = vec![false; text.len() + 1];
+    positions[0] = true;
+    for token in tokens {
+        let mut next = vec![false; text.len() + 1];
+        for start in (0..=text.len()).filter(|&start| positions[start]) {
+            let rest = &text[start..];
+            let single = |c: char| match token {
+                Token::Literal(literal) => c == *literal,
+                Token::AnyChar => c != '/',
+                Token::Bracket(bracket) => c != '/' && bracket.matches(c),
+                _ => false,
+            };
+            match token {
+                Token::Literal(_) | Token::AnyChar | Token::Bracket(_) => {
+                    if rest.first().is_some_and(|&c| single(c)) {
+                        next[start + 1] = true;
+                    }
+                }
+                Token::Star => {
+                    let len = rest.iter().take_while(|&&c| c != '/').count();
+                    next[start..=start + len].fill(true);
+                }
+                Token::AnyPath => next[start..].fill(true),
+                Token::AnyDirs => {
+                    next[start] = true;
+                    for (offset, _) in rest.iter().enumerate().filter(|(_, &c)| c == '/') {
+                        next[start + offset + 1] = true;
+                    }
+                }
+            }
+        }
+        positions = next;
+    }
+    positions[text.len()]
+}
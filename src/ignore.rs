@@ -0,0 +1,123 @@
+use crate::glob::{self, Glob};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The ignore files read in every directory during a recursive search, from the lowest to the highest precedence.
+pub const IGNORE_FILES: [&str; 3] = [".git/info/exclude", ".gitignore", ".ignore"];
+
+/// A line of an ignore file.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    glob: Glob,
+    /// Whether the line started with `!`, so the paths it matches aren't ignored even if a previous rule ignored them.
+    negated: bool,
+    /// Whether the line ended with `/`, so it only matches directories.
+    dir_only: bool,
+    /// Whether the pattern had a `/` before its end, so it is matched against the path relative to the directory
+    /// of the ignore file instead of against the name of the file.
+    anchored: bool,
+}
+
+/// The rules of an ignore file, with the `.gitignore` syntax, which apply to the paths under its directory.
+#[derive(Debug, Clone)]
+pub struct IgnoreFile {
+    dir: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    /// Parses the content of an ignore file found in the directory.
+    ///
+    /// Every line is a glob, see `Glob`. Blank lines and lines starting with `#` are skipped, and a `\` makes
+    /// a leading `#` or `!` literal. A line starting with `!` re-includes the paths a previous line ignored.
+    /// A line ending with `/` only matches directories. A line with a `/` at its start or middle is matched against
+    /// the path relative to the directory, else against the name of the file at any depth.
+    /// The lines with an invalid glob are skipped.
+    pub fn parse(content: &str, dir: &Path) -> IgnoreFile {
+        let rules = content.lines().filter_map(parse_rule).collect();
+        IgnoreFile {
+            dir: dir.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Reads and parses the ignore file at the path, which applies to the given directory.
+    pub fn from_file(path: &Path, dir: &Path) -> io::Result<IgnoreFile> {
+        let content = fs::read(path)?;
+        Ok(IgnoreFile::parse(&String::from_utf8_lossy(&content), dir))
+    }
+
+    /// Reads the ignore files of the directory, see `IGNORE_FILES`, skipping the ones that don't exist.
+    pub fn from_dir(dir: &Path) -> Vec<IgnoreFile> {
+        IGNORE_FILES
+            .iter()
+            .filter_map(|name| IgnoreFile::from_file(&dir.join(name), dir).ok())
+            .collect()
+    }
+
+    /// Returns `Some(true)` if the path is ignored by the last rule that matches it, `Some(false)` if that rule
+    /// is negated, or `None` if no rule matches it or the path isn't under the directory of the file.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = glob::slash_path(path.strip_prefix(&self.dir).ok()?);
+        let name = relative.rsplit('/').next().unwrap_or(&relative);
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| is_dir || !rule.dir_only)
+            .find(|rule| {
+                let text = if rule.anchored { &relative } else { name };
+                rule.glob.matches(text)
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Returns `true` if the last rule of the ignore files that matches the path ignores it.
+/// The files are given from the lowest to the highest precedence, usually from the root to the deepest directory.
+pub fn is_ignored(files: &[Arc<IgnoreFile>], path: &Path, is_dir: bool) -> bool {
+    files
+        .iter()
+        .rev()
+        .find_map(|file| file.matched(path, is_dir))
+        .unwrap_or(false)
+}
+
+/// Parses a line of an ignore file, the escaped `\#` and `\!` are left to the glob, which makes them literal.
+fn parse_rule(line: &str) -> Option<IgnoreRule> {
+    let line = trim_trailing_spaces(line);
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let pattern = line.strip_prefix('/').unwrap_or(line);
+    if pattern.is_empty() {
+        return None;
+    }
+    let glob = Glob::new(pattern).ok()?;
+    Some(IgnoreRule {
+        glob,
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+/// Removes the spaces at the end of the line, except one escaped with `\`.
+fn trim_trailing_spaces(line: &str) -> &str {
+    let trimmed = line.trim_end_matches(' ');
+    if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
@@ -1,11 +1,30 @@
+pub mod aho_corasick;
 pub mod bracket_expression;
+pub mod bytes;
+pub mod cli;
 pub mod error;
 pub mod evaluated_state;
 pub mod file_handler;
+pub mod fuzzy;
+pub mod glob;
+pub mod ignore;
+pub mod matcher;
+pub mod nfa;
+pub mod parallel;
+pub mod pike_vm;
+pub mod prefilter;
 pub mod regex;
+pub mod regex_builder;
 pub mod regex_class;
 pub mod regex_part;
 pub mod regex_rep;
+pub mod regex_set;
 pub mod regex_state;
 pub mod regex_value;
+pub mod replacer;
+pub mod segment;
+pub mod stream;
+pub mod syntax;
+pub mod utf8;
+pub mod walker;
 pub mod match_result;
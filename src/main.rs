@@ -1,29 +1,110 @@
+use grep_rustico::cli::{self, Command, Config};
 use grep_rustico::file_handler::FileHandler;
+use grep_rustico::matcher::Matcher;
+use grep_rustico::parallel;
+use grep_rustico::walker::WalkError;
 use std::env;
+use std::io::{self, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process;
 
-const EXPECTED_ARG_COUNT: usize = 3;
+/// The exit status of grep when the arguments are wrong or a file couldn't be searched.
+const ERROR_STATUS: i32 = 2;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let config = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Search(config)) => *config,
+        Ok(Command::Help) => {
+            print!("{}", cli::HELP);
+            return;
+        }
+        Ok(Command::Version) => {
+            println!("{}", cli::VERSION);
+            return;
+        }
+        Err(err) => {
+            eprintln!("grep: {}", err);
+            eprintln!("{}", cli::USAGE);
+            eprintln!("Try '--help' for more information.");
+            process::exit(ERROR_STATUS);
+        }
+    };
 
-    if args.len() != EXPECTED_ARG_COUNT {
-        eprintln!("Invalid arguments, the format is: <expression> <file>");
-        return;
-    }
+    let matcher = match config.matcher() {
+        Ok(matcher) => matcher,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(ERROR_STATUS);
+        }
+    };
 
-    let expression = &args[1];
+    // The files are searched by several threads, but their output is printed whole and in order.
+    // A file that can't be searched doesn't stop the search of the others.
+    let walker = config.walker();
+    let entries = config
+        .files
+        .iter()
+        .flat_map(|file_name| -> Box<dyn Iterator<Item = _>> {
+            if config.recursive && file_name != "-" {
+                Box::new(walker.walk(file_name))
+            } else {
+                Box::new(iter::once(Ok(PathBuf::from(file_name))))
+            }
+        });
+    // A single file is searched on this thread, printing its lines as they are found.
+    let threads = if config.recursive || config.files.len() > 1 {
+        config.thread_count()
+    } else {
+        1
+    };
+    let mut failed = false;
+    parallel::run_ordered(
+        threads,
+        entries,
+        |entry: Result<PathBuf, WalkError>| match entry {
+            Ok(path) => search(&path, &config, &matcher, threads > 1),
+            Err(err) => Searched {
+                output: vec![],
+                error: Some(err.to_string()),
+            },
+        },
+        |searched| {
+            let _ = io::stdout().lock().write_all(&searched.output);
+            if let Some(err) = searched.error {
+                eprintln!("grep: {}", err);
+                failed = true;
+            }
+        },
+    );
+    if failed {
+        process::exit(ERROR_STATUS);
+    }
+}
 
-    let file_name = &args[2];
+/// The output of the search of a file, and the error that stopped it, if any.
+struct Searched {
+    output: Vec<u8>,
+    error: Option<String>,
+}
 
-    let file_handler = match FileHandler::new(file_name) {
+/// Searches the file, keeping the lines it prints in memory if it is `buffered`, or else printing them right away.
+fn search(path: &Path, config: &Config, matcher: &Matcher, buffered: bool) -> Searched {
+    let mut handler = match FileHandler::with_config(path, config) {
+        Ok(handler) if buffered => handler.buffered(),
         Ok(handler) => handler,
         Err(err) => {
-            eprintln!("grep: {}: {}", file_name, err);
-            return;
+            return Searched {
+                output: vec![],
+                error: Some(format!("{}: {}", path.display(), err)),
+            }
         }
     };
-
-    if let Err(err) = file_handler.process_file(expression) {
-        eprintln!("Error: {}", err);
+    let result = handler.process(matcher);
+    Searched {
+        output: handler.take_output(),
+        error: result
+            .err()
+            .map(|err| format!("{}: {}", path.display(), err)),
     }
 }
@@ -2,7 +2,43 @@
 pub struct MatchResult {
     pub start: usize,
     pub end: usize,
-    pub matched: String 
+    pub matched: String,
+    /// The capturing groups that took part in the match, in the order of their opening parenthesis.
+    pub captures: Vec<Capture>,
+    /// The number of edits needed to turn the matched text into a match of the expression, 0 for exact matches.
+    pub cost: usize,
+    /// The edits of an approximate match, in the order of their position.
+    pub edits: Vec<Edit>,
+}
+
+/// The text captured by a group of the expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    /// The number of the group, starting at 1.
+    pub index: usize,
+    pub name: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub matched: String,
+}
+
+/// A difference between the text of an approximate match and the expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub kind: EditKind,
+    /// The position of the value where the edit is, for a deletion the position where the missing character would be.
+    pub position: usize,
+}
+
+/// The kinds of edits of the Levenshtein distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// The character of the value is different from the one expected by the expression.
+    Substitution,
+    /// The character of the value isn't expected by the expression.
+    Insertion,
+    /// A character expected by the expression is missing from the value.
+    Deletion,
 }
 
 impl MatchResult {
@@ -10,12 +46,51 @@ impl MatchResult {
         MatchResult {
             start,
             end,
-            matched
+            matched,
+            captures: vec![],
+            cost: 0,
+            edits: vec![],
         }
     }
 
+    /// Returns the match with the given edits, its cost being their number.
+    pub fn with_edits(mut self, edits: Vec<Edit>) -> MatchResult {
+        self.cost = edits.len();
+        self.edits = edits;
+        self
+    }
+
+    /// Returns the match with the given captured groups.
+    pub fn with_captures(mut self, captures: Vec<Capture>) -> MatchResult {
+        self.captures = captures;
+        self
+    }
+
     pub fn range(&self) -> (usize, usize) {
         (self.start, self.end)
     }
+
+    /// Returns the capture of the group with the given number, if it took part in the match.
+    pub fn group(&self, index: usize) -> Option<&Capture> {
+        self.captures.iter().find(|capture| capture.index == index)
+    }
+
+    /// Returns the capture of the group with the given name, if it took part in the match.
+    pub fn name(&self, name: &str) -> Option<&Capture> {
+        self.captures
+            .iter()
+            .find(|capture| capture.name.as_deref() == Some(name))
+    }
+
+    /// Returns an iterator over the named groups that took part in the match,
+    /// as their name, their range and the text they captured.
+    pub fn named_groups(&self) -> impl Iterator<Item = (&str, (usize, usize), &str)> {
+        self.captures.iter().filter_map(|capture| {
+            capture
+                .name
+                .as_deref()
+                .map(|name| (name, (capture.start, capture.end), capture.matched.as_str()))
+        })
+    }
 }
 
@@ -0,0 +1,180 @@
+use crate::aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use crate::error::RegexError;
+use crate::match_result::MatchResult;
+use crate::nfa;
+use crate::regex::{ParseOptions, Regex};
+use crate::syntax::Syntax;
+
+/// The engine used to search for the patterns, chosen from the patterns given.
+#[derive(Debug)]
+pub enum Matcher {
+    /// Every pattern is a plain literal, so they are searched with an Aho-Corasick automaton.
+    Literal(AhoCorasick),
+    /// The patterns are searched with the regex engine, as alternatives of a single regex.
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// Creates a new `Matcher` for the given extended regular expressions, a line matches if any of the patterns matches it.
+    /// When every alternative of every pattern is a plain literal, the Aho-Corasick engine is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The regular expressions to search for.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Matcher` if every expression is valid, or the error of the first invalid one.
+    pub fn new<I, S>(patterns: I) -> Result<Self, RegexError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Matcher::with_syntax(patterns, Syntax::Extended)
+    }
+
+    /// Creates a new `Matcher` for the given patterns, written in the given dialect.
+    pub fn with_syntax<I, S>(patterns: I, syntax: Syntax) -> Result<Self, RegexError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Matcher::with_options(patterns, syntax, ParseOptions::default())
+    }
+
+    /// Creates a new `Matcher` for the given patterns, written in the given dialect and parsed with the given options.
+    /// The multiline patterns are always searched with the regex engine, which can match across lines.
+    /// The size limit of the options applies to all the patterns together.
+    pub fn with_options<I, S>(
+        patterns: I,
+        syntax: Syntax,
+        options: ParseOptions,
+    ) -> Result<Self, RegexError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let regexes = patterns
+            .into_iter()
+            .map(|pattern| Regex::with_options(pattern.as_ref(), syntax, options))
+            .collect::<Result<Vec<Regex>, RegexError>>()?;
+        let size = regexes.iter().fold(0, |size: usize, regex| {
+            size.saturating_add(nfa::parts_size(&regex.parts))
+        });
+        if size > options.size_limit {
+            return Err(RegexError::SizeLimitExceeded);
+        }
+        let literals: Option<Vec<Vec<String>>> = if options.multiline {
+            None
+        } else {
+            regexes.iter().map(Regex::literals).collect()
+        };
+        match literals {
+            Some(literals) => Ok(Matcher::Literal(AhoCorasick::new(literals.concat()))),
+            None => Ok(Matcher::Regex(Regex::union(regexes, options.size_limit)?)),
+        }
+    }
+
+    /// Creates a new `Matcher` that searches for the given strings literally, without parsing them as regexes.
+    /// Each string can contain several literals separated by newlines, a line matches if it contains any of them.
+    pub fn fixed_strings<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Matcher::fixed_strings_with_options(patterns, false)
+    }
+
+    /// Creates a new `Matcher` that searches for the given strings literally like `fixed_strings`.
+    /// If `ascii_case_insensitive` is `true`, the ASCII letters match both their uppercase and lowercase forms.
+    pub fn fixed_strings_with_options<I, S>(patterns: I, ascii_case_insensitive: bool) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let literals: Vec<String> = patterns
+            .into_iter()
+            .flat_map(|pattern| {
+                pattern
+                    .as_ref()
+                    .split('\n')
+                    .map(str::to_string)
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+        Matcher::Literal(
+            AhoCorasickBuilder::new()
+                .ascii_case_insensitive(ascii_case_insensitive)
+                .build(literals),
+        )
+    }
+
+    /// Returns the first match in the value, if any.
+    pub fn find(&self, value: &str) -> Result<Option<MatchResult>, RegexError> {
+        match self {
+            Matcher::Literal(automaton) => Ok(automaton.find(value)),
+            Matcher::Regex(regex) => regex.match_expression(value),
+        }
+    }
+
+    /// Returns the start and end of the first match in the haystack, which doesn't need to be valid UTF-8.
+    /// Returns a `MatchLimitExceeded` error if the backtracking engine runs out of steps.
+    pub fn find_bytes(&self, haystack: &[u8]) -> Result<Option<(usize, usize)>, RegexError> {
+        match self {
+            Matcher::Literal(automaton) => Ok(automaton
+                .find_at(haystack, 0)
+                .map(|(_, start, end)| (start, end))),
+            Matcher::Regex(regex) => regex.try_find_range(haystack, 0),
+        }
+    }
+
+    /// Returns the start and end of the first match in the haystack like `find_bytes`,
+    /// but searching with the linear engine, so it never runs out of steps.
+    pub fn find_bytes_linear(&self, haystack: &[u8]) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Literal(automaton) => automaton
+                .find_at(haystack, 0)
+                .map(|(_, start, end)| (start, end)),
+            Matcher::Regex(regex) => regex.search_linear(haystack, 0),
+        }
+    }
+
+    /// Returns the start and end of the best match in the haystack with at most `max_errors` edits,
+    /// see `Regex::search_fuzzy`.
+    /// The Aho-Corasick automaton only finds exact matches, so the literal patterns to search approximately
+    /// must be given as a `Matcher::Regex`.
+    pub fn find_fuzzy(&self, haystack: &[u8], max_errors: usize) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Literal(automaton) => automaton
+                .find_at(haystack, 0)
+                .map(|(_, start, end)| (start, end)),
+            Matcher::Regex(regex) => regex
+                .search_fuzzy(haystack, max_errors)
+                .map(|found| (found.start, found.end)),
+        }
+    }
+
+    /// Sets the number of steps the backtracking engine can take in a single search, `None` removes the limit.
+    /// It has no effect on literal patterns, which are never searched by backtracking.
+    pub fn set_match_limit(&mut self, limit: Option<usize>) {
+        if let Matcher::Regex(regex) = self {
+            regex.set_match_limit(limit);
+        }
+    }
+
+    /// Returns an iterator over the successive non-overlapping matches in the value.
+    pub fn find_iter<'a>(&'a self, value: &'a str) -> Box<dyn Iterator<Item = MatchResult> + 'a> {
+        match self {
+            Matcher::Literal(automaton) => Box::new(automaton.find_iter(value)),
+            Matcher::Regex(regex) => Box::new(regex.find_iter(value)),
+        }
+    }
+
+    /// Checks if the haystack may contain a match, so haystacks that can't match are skipped entirely.
+    pub fn is_candidate(&self, haystack: &[u8]) -> bool {
+        match self {
+            Matcher::Literal(automaton) => automaton.is_match(haystack),
+            Matcher::Regex(regex) => regex.is_candidate(haystack),
+        }
+    }
+}
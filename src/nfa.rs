@@ -0,0 +1,270 @@
+use crate::regex::Regex;
+use crate::regex_part::RegexPart;
+use crate::regex_state::RegexState;
+use crate::regex_value::RegexVal;
+
+/// A single instruction of the compiled automaton.
+#[derive(Debug, Clone)]
+pub enum Inst {
+    /// Consumes one character if it matches the value, then continues with the next instruction.
+    Value(RegexVal),
+    /// Continues with both targets, the first one has priority over the second.
+    Split(usize, usize),
+    /// Continues with the target.
+    Jump(usize),
+    /// Continues with the next instruction only at the start of the input.
+    AssertStart,
+    /// Continues with the next instruction only at the end of the input.
+    AssertEnd,
+    /// Continues with the next instruction only at the start of the input or after a newline.
+    AssertLineStart,
+    /// Continues with the next instruction only at the end of the input or before a newline.
+    AssertLineEnd,
+    /// Stores the current position in the slot.
+    /// It is used both by the repetitions of groups that can match the empty string and by the capturing groups.
+    Mark(usize),
+    /// Continues only if the position moved since it was stored in the slot.
+    /// It stops the repetitions of groups that can match the empty string from looping forever.
+    Progress(usize),
+    /// The pattern with the given index has matched.
+    Match(usize),
+}
+
+/// A Thompson automaton compiled from one or more regular expressions.
+/// Every pattern has its own start instruction, so all of them can be simulated at once
+/// in a single pass over the input.
+#[derive(Debug, Clone, Default)]
+pub struct Nfa {
+    pub insts: Vec<Inst>,
+    /// The start instruction of every pattern.
+    pub starts: Vec<usize>,
+    /// The pattern each instruction belongs to.
+    pub pattern_of: Vec<usize>,
+    /// The number of slots used by the `Mark` and `Progress` instructions.
+    pub slot_count: usize,
+    /// The slots of the capturing groups, in the order of their opening parenthesis.
+    pub groups: Vec<GroupSlots>,
+}
+
+/// The slots where the start and the end of a capturing group are stored while matching.
+#[derive(Debug, Clone)]
+pub struct GroupSlots {
+    pub index: usize,
+    pub name: Option<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Nfa {
+    /// Compiles the given regular expressions into a single automaton.
+    /// The index of each regex in the slice is the index reported by its `Match` instruction.
+    pub fn compile(regexes: &[Regex]) -> Nfa {
+        let mut nfa = Nfa::default();
+        for (pattern, regex) in regexes.iter().enumerate() {
+            nfa.starts.push(nfa.insts.len());
+            nfa.compile_alternatives(&regex.parts);
+            nfa.push(Inst::Match(pattern));
+            nfa.pattern_of.resize(nfa.insts.len(), pattern);
+        }
+        nfa
+    }
+
+    /// Compiles a single part of a regex as an automaton with one pattern.
+    pub fn compile_part(part: &RegexPart) -> Nfa {
+        let mut nfa = Nfa::default();
+        nfa.starts.push(0);
+        nfa.compile_sequence(part);
+        nfa.push(Inst::Match(0));
+        nfa.pattern_of.resize(nfa.insts.len(), 0);
+        nfa
+    }
+
+    /// Returns the number of patterns compiled in the automaton.
+    pub fn pattern_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    fn push(&mut self, inst: Inst) -> usize {
+        self.insts.push(inst);
+        self.insts.len() - 1
+    }
+
+    fn new_slot(&mut self) -> usize {
+        self.slot_count += 1;
+        self.slot_count - 1
+    }
+
+    /// Returns the slots of the capturing group with the given index.
+    /// A group repeated by unrolling its repetition reuses the same slots, so the last repetition is captured.
+    fn group_slots(&mut self, index: usize, name: &Option<String>) -> (usize, usize) {
+        if let Some(group) = self.groups.iter().find(|group| group.index == index) {
+            return (group.start, group.end);
+        }
+        let start = self.new_slot();
+        let end = self.new_slot();
+        self.groups.push(GroupSlots {
+            index,
+            name: name.clone(),
+            start,
+            end,
+        });
+        (start, end)
+    }
+
+    /// Compiles the parts as alternatives, the first parts having priority.
+    fn compile_alternatives(&mut self, parts: &[RegexPart]) {
+        let mut jumps = vec![];
+        for (i, part) in parts.iter().enumerate() {
+            let split = if i + 1 < parts.len() {
+                Some(self.push(Inst::Split(0, 0)))
+            } else {
+                None
+            };
+            self.compile_sequence(part);
+            if let Some(split) = split {
+                jumps.push(self.push(Inst::Jump(0)));
+                self.insts[split] = Inst::Split(split + 1, self.insts.len());
+            }
+        }
+        let end = self.insts.len();
+        for jump in jumps {
+            self.insts[jump] = Inst::Jump(end);
+        }
+    }
+
+    /// Compiles the states of a part one after the other, with its anchors.
+    fn compile_sequence(&mut self, part: &RegexPart) {
+        if part.starts_with_caret {
+            self.push(Inst::AssertStart);
+        }
+        for state in &part.states {
+            self.compile_state(state);
+        }
+        if part.ends_with_dollar {
+            self.push(Inst::AssertEnd);
+        }
+    }
+
+    /// Compiles a state, unrolling its repetition.
+    fn compile_state(&mut self, state: &RegexState) {
+        let (min, max) = state.repetition.bounds();
+        for _ in 0..min {
+            self.compile_value(&state.value);
+        }
+        match max {
+            None => {
+                let split = self.push(Inst::Split(0, 0));
+                let progress_slot = if state.value.can_match_empty() {
+                    let slot = self.new_slot();
+                    self.push(Inst::Mark(slot));
+                    Some(slot)
+                } else {
+                    None
+                };
+                self.compile_value(&state.value);
+                if let Some(slot) = progress_slot {
+                    self.push(Inst::Progress(slot));
+                }
+                self.push(Inst::Jump(split));
+                self.insts[split] = Inst::Split(split + 1, self.insts.len());
+            }
+            Some(max) => {
+                let mut splits = vec![];
+                for _ in min..max {
+                    splits.push(self.push(Inst::Split(0, 0)));
+                    self.compile_value(&state.value);
+                }
+                let end = self.insts.len();
+                for split in splits {
+                    self.insts[split] = Inst::Split(split + 1, end);
+                }
+            }
+        }
+    }
+
+    /// Compiles a single occurrence of a value.
+    fn compile_value(&mut self, value: &RegexVal) {
+        match value {
+            RegexVal::Group {
+                parts,
+                index: Some(index),
+                name,
+            } => {
+                let (start, end) = self.group_slots(*index, name);
+                self.push(Inst::Mark(start));
+                self.compile_alternatives(parts);
+                self.push(Inst::Mark(end));
+            }
+            RegexVal::Group { parts, .. } => self.compile_alternatives(parts),
+            RegexVal::StartOfText => {
+                self.push(Inst::AssertStart);
+            }
+            RegexVal::EndOfText => {
+                self.push(Inst::AssertEnd);
+            }
+            RegexVal::StartOfLine => {
+                self.push(Inst::AssertLineStart);
+            }
+            RegexVal::EndOfLine => {
+                self.push(Inst::AssertLineEnd);
+            }
+            _ => {
+                self.push(Inst::Value(value.clone()));
+            }
+        }
+    }
+}
+
+/// Returns the number of instructions `Nfa::compile_part` produces for a part with the given states,
+/// without compiling them, so the parts that are too big can be rejected before using any memory.
+pub fn part_size(states: &[RegexState], starts_with_caret: bool, ends_with_dollar: bool) -> usize {
+    let anchors = usize::from(starts_with_caret) + usize::from(ends_with_dollar);
+    sequence_size(states, anchors).saturating_add(1)
+}
+
+/// Returns the number of instructions of the programs of all the parts, the alternatives of a regex
+/// or of several ones, which are all kept in memory while searching.
+pub fn parts_size(parts: &[RegexPart]) -> usize {
+    parts.iter().fold(0, |size: usize, part| {
+        size.saturating_add(part_size(
+            &part.states,
+            part.starts_with_caret,
+            part.ends_with_dollar,
+        ))
+    })
+}
+
+fn sequence_size(states: &[RegexState], anchors: usize) -> usize {
+    states.iter().fold(anchors, |size, state| {
+        size.saturating_add(state_size(state))
+    })
+}
+
+fn state_size(state: &RegexState) -> usize {
+    let (min, max) = state.repetition.bounds();
+    let value = value_size(&state.value);
+    let optional = match max {
+        None if state.value.can_match_empty() => value.saturating_add(4),
+        None => value.saturating_add(2),
+        Some(max) => max
+            .saturating_sub(min)
+            .saturating_mul(value.saturating_add(1)),
+    };
+    min.saturating_mul(value).saturating_add(optional)
+}
+
+fn value_size(value: &RegexVal) -> usize {
+    match value {
+        RegexVal::Group { parts, index, .. } => {
+            let alternatives = parts.iter().fold(0, |size: usize, part| {
+                let anchors =
+                    usize::from(part.starts_with_caret) + usize::from(part.ends_with_dollar);
+                size.saturating_add(sequence_size(&part.states, anchors))
+            });
+            let jumps = 2 * parts.len().saturating_sub(1);
+            let marks = if index.is_some() { 2 } else { 0 };
+            alternatives.saturating_add(jumps + marks)
+        }
+        _ => 1,
+    }
+}
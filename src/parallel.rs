@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long an idle worker waits for a new job before looking at the queues again.
+const IDLE_WAIT: Duration = Duration::from_millis(10);
+
+/// Returns the number of threads used when none is given: one per core.
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Runs `work` on every job in a pool of `threads` threads, and passes the results to `done` in the order of the jobs.
+///
+/// Every thread has its own queue, which the jobs are spread over as they come. A thread takes the oldest job
+/// of its queue, and when it is empty it steals the newest job of another one, so no thread stays idle while
+/// there are jobs left. The results are passed to `done` on the calling thread as soon as the ones of all the
+/// previous jobs are, so it can print them while the jobs are still being produced, like by walking a directory.
+/// With a single thread, the jobs are run one after the other on the calling thread.
+pub fn run_ordered<T, R, I, F, D>(threads: usize, jobs: I, work: F, mut done: D)
+where
+    T: Send,
+    R: Send,
+    I: IntoIterator<Item = T>,
+    F: Fn(T) -> R + Sync,
+    D: FnMut(R),
+{
+    if threads <= 1 {
+        jobs.into_iter().for_each(|job| done(work(job)));
+        return;
+    }
+    let queues: Vec<Mutex<VecDeque<(usize, T)>>> =
+        (0..threads).map(|_| Mutex::new(VecDeque::new())).collect();
+    let closed = AtomicBool::new(false);
+    let signal = (Mutex::new(()), Condvar::new());
+    let (sender, receiver) = mpsc::channel::<(usize, R)>();
+    let mut ordered = Ordered::new();
+
+    thread::scope(|scope| {
+        for id in 0..threads {
+            let sender = sender.clone();
+            let (queues, closed, signal, work) = (&queues, &closed, &signal, &work);
+            scope.spawn(move || loop {
+                if let Some((index, job)) = next_job(queues, id) {
+                    if sender.send((index, work(job))).is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                if closed.load(Ordering::Acquire) && queues_empty(queues) {
+                    return;
+                }
+                let guard = signal.0.lock().unwrap_or_else(|err| err.into_inner());
+                let _ = signal.1.wait_timeout(guard, IDLE_WAIT);
+            });
+        }
+        drop(sender);
+
+        for (index, job) in jobs.into_iter().enumerate() {
+            lock(&queues[index % threads]).push_back((index, job));
+            signal.1.notify_one();
+            while let Ok((index, result)) = receiver.try_recv() {
+                ordered.push(index, result, &mut done);
+            }
+        }
+        closed.store(true, Ordering::Release);
+        signal.1.notify_all();
+        for (index, result) in receiver {
+            ordered.push(index, result, &mut done);
+        }
+    });
+}
+
+/// Takes the oldest job of the thread's own queue, or else steals the newest one of another queue.
+fn next_job<T>(queues: &[Mutex<VecDeque<(usize, T)>>], id: usize) -> Option<(usize, T)> {
+    if let Some(job) = lock(&queues[id]).pop_front() {
+        return Some(job);
+    }
+    (1..queues.len())
+        .map(|offset| (id + offset) % queues.len())
+        .find_map(|other| lock(&queues[other]).pop_back())
+}
+
+fn queues_empty<T>(queues: &[Mutex<VecDeque<(usize, T)>>]) -> bool {
+    queues.iter().all(|queue| lock(queue).is_empty())
+}
+
+/// Locks the mutex, even if a thread panicked while holding it, since the queues stay valid.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// The results that arrived before the ones of some previous jobs, waiting for them to be passed on.
+struct Ordered<R> {
+    next: usize,
+    waiting: BTreeMap<usize, R>,
+}
+
+impl<R> Ordered<R> {
+    fn new() -> Ordered<R> {
+        Ordered {
+            next: 0,
+            waiting: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the result of a job, and passes on every result that is next in order.
+    fn push<D: FnMut(R)>(&mut self, index: usize, result: R, done: &mut D) {
+        self.waiting.insert(index, result);
+        while let Some(result) = self.waiting.remove(&self.next) {
+            done(result);
+            self.next += 1;
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use crate::nfa::{Inst, Nfa};
+use crate::utf8;
+
+/// A thread of the simulation: the instruction it is at and the position where its match started.
+#[derive(Debug, Clone, Copy)]
+struct Thread {
+    pc: usize,
+    start: usize,
+}
+
+/// The threads alive at a position of the input, in priority order.
+/// The sparse set keeps track of the visited instructions, so each one is added only once.
+struct ThreadList {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+    threads: Vec<Thread>,
+    /// The earliest start of the threads stopped at an anchor that depends on the input not seen yet.
+    pending: Option<usize>,
+}
+
+/// The input simulated by the automaton, which can be a chunk of a longer stream.
+/// The anchors at the start and at the end of the chunk only hold where the chunk is the start or the end of the stream.
+struct Input<'a> {
+    value: &'a [u8],
+    is_start: bool,
+    is_end: bool,
+}
+
+impl ThreadList {
+    fn new(size: usize) -> ThreadList {
+        ThreadList {
+            dense: Vec::with_capacity(size),
+            sparse: vec![0; size],
+            threads: Vec::with_capacity(size),
+            pending: None,
+        }
+    }
+
+    fn contains(&self, pc: usize) -> bool {
+        let i = self.sparse[pc];
+        i < self.dense.len() && self.dense[i] == pc
+    }
+
+    fn insert(&mut self, pc: usize) {
+        self.sparse[pc] = self.dense.len();
+        self.dense.push(pc);
+    }
+
+    fn clear(&mut self) {
+        self.dense.clear();
+        self.threads.clear();
+        self.pending = None;
+    }
+}
+
+/// Simulates the automaton over the value, running every pattern at once.
+/// The value is stepped a character at a time, or a byte at a time where it isn't valid UTF-8.
+/// It returns the start and end of the match of every pattern, or `None` for the patterns that didn't match.
+///
+/// Each pattern reports its leftmost match, preferring the alternatives and repetitions in the same
+/// order as the backtracking engine does.
+/// If `earliest` is `true`, the search of a pattern stops as soon as any match is found, so only
+/// the presence of the match is meaningful.
+pub fn search(nfa: &Nfa, value: &[u8], earliest: bool) -> Vec<Option<(usize, usize)>> {
+    search_at(nfa, value, 0, earliest)
+}
+
+/// Simulates the automaton over the value like `search`, but only for the matches that start at or after `at`.
+/// The anchors still refer to the whole value.
+pub fn search_at(
+    nfa: &Nfa,
+    value: &[u8],
+    at: usize,
+    earliest: bool,
+) -> Vec<Option<(usize, usize)>> {
+    let pattern_count = nfa.pattern_count();
+    let mut matches: Vec<Option<(usize, usize)>> = vec![None; pattern_count];
+    let mut current = ThreadList::new(nfa.insts.len());
+    let mut next = ThreadList::new(nfa.insts.len());
+    let mut stack = vec![];
+    let mut pos = at;
+    let input = Input {
+        value,
+        is_start: true,
+        is_end: true,
+    };
+
+    loop {
+        for (pattern, &start) in nfa.starts.iter().enumerate() {
+            if matches[pattern].is_none() {
+                add_thread(nfa, &mut current, &mut stack, start, pos, pos, &input);
+            }
+        }
+        let mut cut = vec![false; pattern_count];
+        for i in 0..current.threads.len() {
+            let thread = current.threads[i];
+            let pattern = nfa.pattern_of[thread.pc];
+            if cut[pattern] || (earliest && matches[pattern].is_some()) {
+                continue;
+            }
+            match &nfa.insts[thread.pc] {
+                Inst::Match(_) => {
+                    matches[pattern] = Some((thread.start, pos));
+                    cut[pattern] = true;
+                }
+                Inst::Value(value_to_match) => {
+                    let size = value_to_match.matches(&value[pos..]);
+                    if size > 0 {
+                        add_thread(
+                            nfa,
+                            &mut next,
+                            &mut stack,
+                            thread.pc + 1,
+                            thread.start,
+                            pos + size,
+                            &input,
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+        let all_matched = matches.iter().all(|m| m.is_some());
+        if pos == value.len() || (all_matched && (earliest || next.threads.is_empty())) {
+            break;
+        }
+        pos += utf8::char_len(&value[pos..]);
+        std::mem::swap(&mut current, &mut next);
+        next.clear();
+    }
+    matches
+}
+
+/// The outcome of searching a chunk of a stream with `search_stream`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamSearch {
+    /// The start and end of the leftmost match, which the rest of the stream can't change.
+    Match(usize, usize),
+    /// There is no match at or after the position searched, up to the end of the stream.
+    NoMatch,
+    /// The outcome depends on the input after the chunk.
+    /// The threads still alive only need the input from the given position, so the input before it can be discarded.
+    NeedMore(usize),
+}
+
+/// The search of a stream in progress, kept from one chunk to the next so every byte is simulated only once.
+/// Its positions are relative to the chunk, see `shift`.
+#[derive(Debug, Default)]
+pub struct StreamState {
+    /// Whether a search is in progress, otherwise the next one starts at the position given to `search_stream`.
+    searching: bool,
+    /// The position where the search stopped, at the end of the last chunk.
+    pos: usize,
+    /// The threads that reached that position, in priority order, which are followed again with the input after it.
+    seeds: Vec<Thread>,
+    /// The match found before that position, which the threads of a higher priority can still replace.
+    matched: Option<(usize, usize)>,
+}
+
+impl StreamState {
+    /// Moves the positions of the search back by the number of bytes discarded from the start of the chunk.
+    pub fn shift(&mut self, discarded: usize) {
+        if !self.searching {
+            return;
+        }
+        self.pos -= discarded;
+        for seed in &mut self.seeds {
+            seed.start -= discarded;
+        }
+        if let Some((start, end)) = &mut self.matched {
+            *start -= discarded;
+            *end -= discarded;
+        }
+    }
+}
+
+/// Simulates the automaton of a single pattern over a chunk of a stream, for the matches that start at or after `at`.
+/// `is_start` and `is_end` tell whether the chunk is at the start and at the end of the stream.
+/// If the chunk isn't at the start of the stream, `^` can't match at its first position, so the caller
+/// must keep the byte before the position searched for the line anchors to see it.
+///
+/// The search stops at the end of the chunk, or before a character cut by it, and reports that it needs
+/// more input whenever a thread alive there could still change the match. The threads are then kept in the state,
+/// and the next call, with the same chunk followed by more input, resumes the search where it stopped instead of at `at`.
+pub fn search_stream(
+    nfa: &Nfa,
+    chunk: &[u8],
+    at: usize,
+    state: &mut StreamState,
+    is_start: bool,
+    is_end: bool,
+) -> StreamSearch {
+    let mut current = ThreadList::new(nfa.insts.len());
+    let mut next = ThreadList::new(nfa.insts.len());
+    let mut stack = vec![];
+    let input = Input {
+        value: chunk,
+        is_start,
+        is_end,
+    };
+    if !state.searching {
+        state.searching = true;
+        state.pos = at;
+        state.seeds.clear();
+        state.matched = None;
+    }
+    let mut pos = state.pos;
+    let mut matched = state.matched;
+    let mut seeds = std::mem::take(&mut state.seeds);
+    let mut next_seeds = vec![];
+    for seed in &seeds {
+        add_thread(
+            nfa,
+            &mut current,
+            &mut stack,
+            seed.pc,
+            seed.start,
+            pos,
+            &input,
+        );
+    }
+
+    loop {
+        if !is_end && is_cut(&chunk[pos..]) {
+            let mut waiting = current.pending;
+            let mut found = matched;
+            for thread in &current.threads {
+                if let Inst::Match(_) = nfa.insts[thread.pc] {
+                    found = Some((thread.start, pos));
+                    break;
+                }
+                waiting = Some(waiting.map_or(thread.start, |waiting| waiting.min(thread.start)));
+            }
+            if let (None, Some((start, end))) = (waiting, found) {
+                state.searching = false;
+                return StreamSearch::Match(start, end);
+            }
+            let keep = seeds
+                .iter()
+                .map(|seed| seed.start)
+                .chain(matched.map(|(start, _)| start))
+                .min()
+                .unwrap_or(pos);
+            state.pos = pos;
+            state.seeds = seeds;
+            state.matched = matched;
+            return StreamSearch::NeedMore(keep);
+        }
+        if matched.is_none() {
+            add_thread(
+                nfa,
+                &mut current,
+                &mut stack,
+                nfa.starts[0],
+                pos,
+                pos,
+                &input,
+            );
+            seeds.push(Thread {
+                pc: nfa.starts[0],
+                start: pos,
+            });
+        }
+        for i in 0..current.threads.len() {
+            let thread = current.threads[i];
+            match &nfa.insts[thread.pc] {
+                Inst::Match(_) => {
+                    matched = Some((thread.start, pos));
+                    break;
+                }
+                Inst::Value(value_to_match) => {
+                    let size = value_to_match.matches(&chunk[pos..]);
+                    if size > 0 {
+                        add_thread(
+                            nfa,
+                            &mut next,
+                            &mut stack,
+                            thread.pc + 1,
+                            thread.start,
+                            pos + size,
+                            &input,
+                        );
+                        next_seeds.push(Thread {
+                            pc: thread.pc + 1,
+                            start: thread.start,
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        let finished = next.threads.is_empty() && next.pending.is_none();
+        if pos == chunk.len() || (matched.is_some() && finished) {
+            break;
+        }
+        pos += utf8::char_len(&chunk[pos..]);
+        std::mem::swap(&mut current, &mut next);
+        std::mem::swap(&mut seeds, &mut next_seeds);
+        next.clear();
+        next_seeds.clear();
+    }
+    state.searching = false;
+    match matched {
+        Some((start, end)) => StreamSearch::Match(start, end),
+        None => StreamSearch::NoMatch,
+    }
+}
+
+/// Returns `true` if the bytes are the start of a character that continues after them,
+/// or if there are no bytes at all.
+fn is_cut(rest: &[u8]) -> bool {
+    rest.len() < 4 && utf8::decode_char(rest).is_none()
+}
+
+/// Adds a thread to the list, following the instructions that don't consume input.
+/// The instructions are visited depth first, so the threads keep the priority of the split branches.
+/// The threads stopped at an anchor that depends on the input after the end of a chunk are recorded as pending.
+fn add_thread(
+    nfa: &Nfa,
+    list: &mut ThreadList,
+    stack: &mut Vec<usize>,
+    pc: usize,
+    start: usize,
+    pos: usize,
+    input: &Input,
+) {
+    let value = input.value;
+    let at_end = pos == value.len();
+    stack.push(pc);
+    while let Some(pc) = stack.pop() {
+        if list.contains(pc) {
+            continue;
+        }
+        list.insert(pc);
+        match nfa.insts[pc] {
+            Inst::Split(first, second) => {
+                stack.push(second);
+                stack.push(first);
+            }
+            Inst::Jump(target) => stack.push(target),
+            Inst::AssertStart if pos == 0 && input.is_start => stack.push(pc + 1),
+            Inst::AssertEnd if at_end && input.is_end => stack.push(pc + 1),
+            Inst::AssertLineStart
+                if (pos == 0 && input.is_start) || (pos > 0 && value[pos - 1] == b'\n') =>
+            {
+                stack.push(pc + 1)
+            }
+            Inst::AssertLineEnd if (at_end && input.is_end) || (!at_end && value[pos] == b'\n') => {
+                stack.push(pc + 1)
+            }
+            Inst::AssertEnd | Inst::AssertLineEnd if at_end => {
+                list.pending = Some(list.pending.map_or(start, |pending| pending.min(start)));
+            }
+            Inst::AssertStart | Inst::AssertEnd | Inst::AssertLineStart | Inst::AssertLineEnd => (),
+            Inst::Mark(_) | Inst::Progress(_) => stack.push(pc + 1),
+            Inst::Value(_) | Inst::Match(_) => list.threads.push(Thread { pc, start }),
+        }
+    }
+}
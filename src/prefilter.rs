@@ -0,0 +1,105 @@
+/// Number of bytes inspected at once by `memchr`.
+const WORD_SIZE: usize = std::mem::size_of::<u64>();
+/// A word with the lowest bit of every byte set.
+const LO_BITS: u64 = u64::from_ne_bytes([0x01; WORD_SIZE]);
+/// A word with the highest bit of every byte set.
+const HI_BITS: u64 = u64::from_ne_bytes([0x80; WORD_SIZE]);
+/// The base of the Rabin-Karp rolling hash, larger than any byte so every byte of the window
+/// still weighs on the hash, however long the needle is.
+const HASH_BASE: u32 = 257;
+
+/// A `Finder` searches for a fixed sequence of bytes (the needle) inside a haystack.
+/// It is used as a prefilter: the regex engine only runs on inputs that contain the literals
+/// every match requires.
+/// Single byte needles are searched with `memchr`, longer ones with the Rabin-Karp algorithm.
+#[derive(Debug, Clone)]
+pub struct Finder {
+    needle: Vec<u8>,
+    hash: u32,
+    /// `HASH_BASE` to the power of the length of the needle minus one, the weight of the first byte of a window.
+    hash_pow: u32,
+}
+
+impl Finder {
+    /// Creates a new `Finder` for the given needle.
+    pub fn new(needle: &[u8]) -> Finder {
+        let mut hash_pow: u32 = 1;
+        for _ in 1..needle.len() {
+            hash_pow = hash_pow.wrapping_mul(HASH_BASE);
+        }
+        Finder {
+            needle: needle.to_vec(),
+            hash: rolling_hash(needle),
+            hash_pow,
+        }
+    }
+
+    /// Returns the needle this finder searches for.
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Returns the position of the first occurrence of the needle in the haystack, if any.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        match self.needle.len() {
+            0 => Some(0),
+            1 => memchr(self.needle[0], haystack),
+            n if n > haystack.len() => None,
+            _ => self.rabin_karp(haystack),
+        }
+    }
+
+    /// Rabin-Karp search with a rolling hash, the bytes are only compared when the hashes are equal.
+    fn rabin_karp(&self, haystack: &[u8]) -> Option<usize> {
+        let n = self.needle.len();
+        let mut hash = rolling_hash(&haystack[..n]);
+        let mut i = 0;
+        loop {
+            if hash == self.hash && haystack[i..i + n] == self.needle[..] {
+                return Some(i);
+            }
+            if i + n >= haystack.len() {
+                return None;
+            }
+            hash = hash
+                .wrapping_sub(self.hash_pow.wrapping_mul(haystack[i] as u32))
+                .wrapping_mul(HASH_BASE)
+                .wrapping_add(haystack[i + n] as u32);
+            i += 1;
+        }
+    }
+}
+
+/// Returns the Rabin-Karp hash of the bytes, a polynomial in `HASH_BASE` computed with wrapping arithmetic.
+fn rolling_hash(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |hash: u32, &b| {
+        hash.wrapping_mul(HASH_BASE).wrapping_add(b as u32)
+    })
+}
+
+/// Returns the position of the first occurrence of `byte` in the haystack.
+/// The haystack is inspected a word at a time, looking for a zero byte in the word xor-ed with the
+/// repeated needle byte.
+pub fn memchr(byte: u8, haystack: &[u8]) -> Option<usize> {
+    let repeated = LO_BITS.wrapping_mul(byte as u64);
+    let mut chunks = haystack.chunks_exact(WORD_SIZE);
+    let mut offset = 0;
+    for chunk in chunks.by_ref() {
+        let mut word = [0; WORD_SIZE];
+        word.copy_from_slice(chunk);
+        if has_zero_byte(u64::from_ne_bytes(word) ^ repeated) {
+            return chunk.iter().position(|&b| b == byte).map(|i| offset + i);
+        }
+        offset += WORD_SIZE;
+    }
+    chunks
+        .remainder()
+        .iter()
+        .position(|&b| b == byte)
+        .map(|i| offset + i)
+}
+
+/// Returns `true` if any byte of the word is zero.
+fn has_zero_byte(word: u64) -> bool {
+    word.wrapping_sub(LO_BITS) & !word & HI_BITS != 0
+}
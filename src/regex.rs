@@ -1,21 +1,108 @@
+use crate::bracket_expression::BracketExpression;
 use crate::error::RegexError;
+use crate::fuzzy::{self, FuzzyMatch, Myers};
 use crate::match_result::MatchResult;
+use crate::nfa::{self, Nfa};
+use crate::pike_vm;
 use crate::regex_class::RegexClass;
-use crate::regex_part::RegexPart;
+use crate::regex_part::{Backtracker, RegexPart, Slots};
 use crate::regex_rep::RegexRep;
 use crate::regex_state::RegexState;
 use crate::regex_value::RegexVal;
+use crate::replacer::Replacer;
+use crate::segment::{Segment, SegmentKind};
+use crate::stream::{StreamFindIter, DEFAULT_CHUNK_SIZE};
+use crate::syntax::{self, GroupKind, Syntax, Token};
+use crate::utf8;
+use std::cell::RefCell;
+use std::io::Read;
+use std::sync::OnceLock;
 use std::vec;
 
 /// Representation of a regular expression, using a vector of `RegexPart` to represent the different parts of the regex.
-/// If the regex contains the `|` operator, it will be split into different `RegexPart` objects.
+/// If the regex contains the `|` operator outside of a group, it will be split into different `RegexPart` objects.
 #[derive(Debug)]
 pub struct Regex {
     pub parts: Vec<RegexPart>,
+    /// The number of steps the backtracking engine can take in a single search, or `None` for no limit.
+    pub match_limit: Option<usize>,
+    /// The engine used to search the matches.
+    pub engine: Engine,
+    /// The parts compiled into a single automaton for the linear engine, compiled the first time it is used.
+    linear_program: OnceLock<Nfa>,
+}
+
+/// The engine used by a `Regex` to search the matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// The backtracking engine, which reports the captures of the groups.
+    /// It falls back to the linear engine when a search exceeds the match limit.
+    #[default]
+    Backtracking,
+    /// The Pike VM, which takes linear time in the length of the value whatever the expression,
+    /// but doesn't report the captures of the groups.
+    PikeVm,
+}
+
+/// A match found by the backtracking engine, with the part that matched and the slots filled by its program.
+#[derive(Debug)]
+pub struct PartMatch<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub part: &'a RegexPart,
+    pub slots: Slots,
+}
+
+impl PartMatch<'_> {
+    /// Returns the match in the value, with the text captured by the groups of the part.
+    pub fn to_match_result(&self, value: &str) -> MatchResult {
+        MatchResult::new(
+            self.start,
+            self.end,
+            value[self.start..self.end].to_string(),
+        )
+        .with_captures(self.part.captures(value, &self.slots))
+    }
+}
+
+/// The default number of steps the backtracking engine can take in a single search.
+pub const DEFAULT_MATCH_LIMIT: usize = 10_000_000;
+
+/// The memory of the backtracking searches: the indexes of the parts that can match the haystack,
+/// and what their programs work with.
+#[derive(Default)]
+struct SearchCache {
+    candidates: Vec<usize>,
+    backtracker: Backtracker,
+}
+
+thread_local! {
+    /// The memory of the backtracking searches of every thread, reused by all of them,
+    /// so searching line after line doesn't allocate it for every line.
+    static SEARCH_CACHE: RefCell<SearchCache> = RefCell::new(SearchCache::default());
 }
 
 impl Regex {
-    /// Creates a new `Regex` from a given expression. It contains the main parse logic of a regex.
+    /// Creates a new `Regex` from its parts, with the default match limit and engine.
+    /// The parts are compiled for the backtracking engine, but not the parts of the groups inside them.
+    pub fn from_parts(mut parts: Vec<RegexPart>) -> Self {
+        for part in &mut parts {
+            part.compile();
+        }
+        Regex {
+            parts,
+            match_limit: Some(DEFAULT_MATCH_LIMIT),
+            engine: Engine::default(),
+            linear_program: OnceLock::new(),
+        }
+    }
+
+    /// Sets the number of steps the backtracking engine can take in a single search, `None` removes the limit.
+    pub fn set_match_limit(&mut self, limit: Option<usize>) {
+        self.match_limit = limit;
+    }
+
+    /// Creates a new `Regex` from a given extended regular expression.
     ///
     /// # Arguments
     ///
@@ -26,68 +113,670 @@ impl Regex {
     /// A `Result` containing the `Regex` instance if the expression is valid, or a RegexError if the expression is invalid.
     ///
     pub fn new(expression: &str) -> Result<Self, RegexError> {
-        let expressions: Vec<&str> = expression.split('|').collect();
-        let mut parts: Vec<RegexPart> = vec![];
-
-        for expr in expressions {
-            let mut states: Vec<RegexState> = vec![];
-            let mut chars_iter = expr.chars();
-            let ends_with_dollar = expr.ends_with('$');
-            if !expr.starts_with('^') {
-                states.push(RegexState {
-                    value: RegexVal::Wildcard,
-                    repetition: RegexRep::Any,
-                });
-            }
-            while let Some(c) = chars_iter.next() {
-                let state = match c {
-                    '.' => parse_dot(),
-                    '*' => parse_star(&mut states),
-                    '\\' => parse_backslash(&mut chars_iter),
-                    '?' => parse_question(&mut states),
-                    '+' => parse_plus(&mut states),
-                    '^' => parse_caret(expr),
-                    '$' => parse_dollar(&mut chars_iter),
-                    '|' => continue,
-                    '[' => parse_bracket(&mut chars_iter),
-                    '{' => parse_curly_bracket(&mut chars_iter, &mut states),
-                    _ => parse_literal(c),
-                };
-                match state {
-                    Ok(Some(s)) => states.push(s),
-                    Ok(None) => (),
-                    Err(err) => return Err(err),
+        Regex::with_syntax(expression, Syntax::Extended)
+    }
+
+    /// Creates a new `Regex` from an expression written in the given dialect. It contains the main parse logic of a regex.
+    /// The expression is split into tokens according to the dialect, and the tokens are parsed into `RegexPart` objects,
+    /// so every dialect produces the same representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - A string slice representing the regular expression.
+    /// * `syntax` - The dialect the expression is written in.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Regex` instance if the expression is valid, or a RegexError if the expression is invalid.
+    pub fn with_syntax(expression: &str, syntax: Syntax) -> Result<Self, RegexError> {
+        Regex::with_options(expression, syntax, ParseOptions::default())
+    }
+
+    /// Creates a new `Regex` from an expression written in the given dialect, parsed with the given options.
+    pub fn with_options(
+        expression: &str,
+        syntax: Syntax,
+        options: ParseOptions,
+    ) -> Result<Self, RegexError> {
+        let tokens = syntax::tokenize(expression, syntax, &options)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+            group_count: 0,
+            group_names: vec![],
+            options,
+        };
+        let parts = parser.parse_alternatives()?;
+        if parser.position < parser.tokens.len() {
+            return Err(RegexError::UnmatchedParenthesis);
+        }
+        if nfa::parts_size(&parts) > parser.options.size_limit {
+            return Err(RegexError::SizeLimitExceeded);
+        }
+        Ok(Regex::from_parts(parts))
+    }
+
+    /// Creates a new `Regex` that matches the given string literally, without parsing it.
+    /// Every character, including the metacharacters, only matches itself.
+    /// The string can contain several literals separated by newlines, the regex matches any of them.
+    pub fn literal(literal: &str) -> Self {
+        let parts = literal
+            .split('\n')
+            .map(|line| {
+                let states = line
+                    .chars()
+                    .map(|c| RegexState {
+                        value: RegexVal::Literal(c),
+                        repetition: RegexRep::Exact(1),
+                    })
+                    .collect();
+                RegexPart::new(states, false, false)
+            })
+            .collect();
+        Regex::from_parts(parts)
+    }
+
+    /// Creates a new `Regex` that matches any of the regexes, like their expressions joined with `|`.
+    /// The capturing groups are numbered across all of them, so the groups of a regex come after the ones
+    /// of the previous regexes, and `$1` always refers to a single group.
+    /// Returns an `InvalidGroupName` error if several regexes have a group with the same name,
+    /// and a `SizeLimitExceeded` error if their programs together are bigger than the size limit.
+    pub fn union(regexes: Vec<Regex>, size_limit: usize) -> Result<Self, RegexError> {
+        let mut parts = vec![];
+        let mut names: Vec<String> = vec![];
+        for regex in regexes {
+            for name in regex.capture_names() {
+                if names.iter().any(|other| other == name) {
+                    return Err(RegexError::InvalidGroupName);
                 }
+                names.push(name.to_string());
             }
-            parts.push(RegexPart {
-                states,
-                ends_with_dollar,
-            });
+            let offset = group_count(&parts);
+            parts.extend(renumber_groups(regex.parts, offset));
         }
-        Ok(Regex { parts })
+        if nfa::parts_size(&parts) > size_limit {
+            return Err(RegexError::SizeLimitExceeded);
+        }
+        Ok(Regex::from_parts(parts))
     }
 
     /// Matches the given value against the regular expression.
-    /// It iterates over the different `RegexPart` objects and tries to match the value against each one.
+    /// It tries every position of the value from left to right, and at each one it tries to match
+    /// the different `RegexPart` objects in order, so the leftmost match is returned.
+    /// The parts whose required literal doesn't appear in the value are skipped.
     /// # Arguments
     ///
     /// * `value` - A string slice to match against the regular expression.
     ///
     /// # Returns
     ///
-    /// A `Result` containing `true` if the string matches the regular expression, or `false` otherwise.
+    /// A `Result` containing the first match of the regular expression in the string, if any,
+    /// or a `MatchLimitExceeded` error if the backtracking engine ran out of steps.
     pub fn match_expression(&self, value: &str) -> Result<Option<MatchResult>, RegexError> {
-        if !value.is_ascii() {
-            return Err(RegexError::NonAsciiInput);
+        if self.engine == Engine::PikeVm {
+            return Ok(self.find_at(value, 0));
         }
+        let found = self.try_search(value.as_bytes(), 0)?;
+        Ok(found.map(|found| found.to_match_result(value)))
+    }
 
-        for part in &self.parts {
-            match part.match_single_expression(value)? {
-                Some(result) => return Ok(Some(result)),
-                None => continue,
+    /// Returns the leftmost match that starts at or after the position `at` of the value.
+    /// The anchors still refer to the whole value, so `^` can only match if `at` is 0.
+    /// If the backtracking engine runs out of steps, the match is searched with the linear engine
+    /// instead, which doesn't report the captures of the groups.
+    pub fn find_at(&self, value: &str, at: usize) -> Option<MatchResult> {
+        if self.engine == Engine::Backtracking {
+            if let Ok(found) = self.try_search(value.as_bytes(), at) {
+                return found.map(|found| found.to_match_result(value));
             }
-        } 
-        Ok(None)
+        }
+        self.search_linear(value.as_bytes(), at)
+            .map(|(start, end)| MatchResult::new(start, end, value[start..end].to_string()))
+    }
+
+    /// Returns the start and end of the leftmost match that starts at or after the position `at` of the haystack.
+    /// If the backtracking engine runs out of steps, the match is searched with the linear engine instead.
+    pub fn search(&self, haystack: &[u8], at: usize) -> Option<(usize, usize)> {
+        self.try_find_range(haystack, at)
+            .unwrap_or_else(|_| self.search_linear(haystack, at))
+    }
+
+    /// Returns the start and end of the leftmost match that starts at or after the position `at` of the haystack,
+    /// searched with the engine of the regex.
+    /// Returns a `MatchLimitExceeded` error if the backtracking engine runs out of steps.
+    pub fn try_find_range(
+        &self,
+        haystack: &[u8],
+        at: usize,
+    ) -> Result<Option<(usize, usize)>, RegexError> {
+        match self.engine {
+            Engine::Backtracking => Ok(self
+                .try_search(haystack, at)?
+                .map(|found| (found.start, found.end))),
+            Engine::PikeVm => Ok(self.search_linear(haystack, at)),
+        }
+    }
+
+    /// Searches the leftmost match that starts at or after the position `at` of the haystack with the backtracking engine,
+    /// whatever the engine of the regex.
+    /// It returns the match with the part that matched and the slots filled by its program,
+    /// or a `MatchLimitExceeded` error if the search takes more steps than the match limit.
+    ///
+    /// The start positions are tried a character at a time, or a byte at a time where the haystack
+    /// isn't valid UTF-8, and at each one the parts are tried in order.
+    /// The parts whose required literal doesn't appear in the haystack are skipped.
+    /// The memory the search works with is kept for the next searches of the thread.
+    pub fn try_search(
+        &self,
+        haystack: &[u8],
+        at: usize,
+    ) -> Result<Option<PartMatch<'_>>, RegexError> {
+        SEARCH_CACHE.with(|cache| {
+            let SearchCache {
+                candidates,
+                backtracker,
+            } = &mut *cache.borrow_mut();
+            candidates.clear();
+            candidates.extend(
+                (0..self.parts.len()).filter(|&i| self.parts[i].is_candidate(&haystack[at..])),
+            );
+            if candidates.is_empty() {
+                return Ok(None);
+            }
+            let mut steps_left = self.match_limit.unwrap_or(usize::MAX);
+            let mut start = at;
+            loop {
+                for &i in candidates.iter() {
+                    let part = &self.parts[i];
+                    if part.starts_with_caret && start > 0 {
+                        continue;
+                    }
+                    if let Some((end, slots)) =
+                        part.run(haystack, start, &mut steps_left, backtracker)?
+                    {
+                        return Ok(Some(PartMatch {
+                            start,
+                            end,
+                            part,
+                            slots,
+                        }));
+                    }
+                }
+                if start == haystack.len() {
+                    return Ok(None);
+                }
+                start += utf8::char_len(&haystack[start..]);
+            }
+        })
+    }
+
+    /// Searches the leftmost match that starts at or after the position `at` of the haystack with the Pike VM,
+    /// which takes linear time in the length of the haystack whatever the expression.
+    /// It finds the same match as the backtracking engine.
+    pub fn search_linear(&self, haystack: &[u8], at: usize) -> Option<(usize, usize)> {
+        pike_vm::search_at(self.linear_program(), haystack, at, false)[0]
+    }
+
+    /// Returns the best match in the value with at most `max_errors` edits, counted as in the Levenshtein distance,
+    /// with its cost and its edits, see `search_fuzzy`.
+    /// The match doesn't report the captures of the groups.
+    pub fn find_fuzzy(&self, value: &str, max_errors: usize) -> Option<MatchResult> {
+        self.search_fuzzy(value.as_bytes(), max_errors)
+            .map(|found| {
+                MatchResult::new(
+                    found.start,
+                    found.end,
+                    value[found.start..found.end].to_string(),
+                )
+                .with_edits(found.edits)
+            })
+    }
+
+    /// Searches the best match in the haystack with at most `max_errors` edits: the one with the fewest edits,
+    /// then the one that starts the earliest, then the longest one.
+    /// The regexes that are a short literal are searched with a bit-parallel algorithm,
+    /// and the other ones by simulating their automaton with error states.
+    pub fn search_fuzzy(&self, haystack: &[u8], max_errors: usize) -> Option<FuzzyMatch> {
+        let literal = match self.parts.as_slice() {
+            [part] => part.as_literal().and_then(|literal| Myers::new(&literal)),
+            _ => None,
+        };
+        match literal {
+            Some(myers) => myers.find(haystack, max_errors),
+            None => fuzzy::search(self.linear_program(), haystack, max_errors),
+        }
+    }
+
+    /// Returns the automaton run by the Pike VM, compiling it the first time it is needed.
+    fn linear_program(&self) -> &Nfa {
+        self.linear_program
+            .get_or_init(|| Nfa::compile(std::slice::from_ref(self)))
+    }
+
+    /// Returns an iterator over the successive non-overlapping matches in the stream, from left to right,
+    /// with their absolute byte offsets in the stream.
+    /// The stream is read in chunks of `DEFAULT_CHUNK_SIZE` bytes and searched with the Pike VM, without splitting
+    /// it in lines, so a match can span several lines and cross the boundary between two chunks.
+    ///
+    /// The memory used is bounded by the chunk size only while no match is in progress. A match that can go on
+    /// to the end of the stream keeps everything read since its start in memory, up to `DEFAULT_BUFFER_LIMIT` bytes,
+    /// past which the iterator returns an error. By default `.` matches newlines, so a pattern with `.*` buffers
+    /// the rest of the stream. Build the regex with `RegexBuilder::dot_matches_newline(false)` to keep the matches
+    /// of `.` within a line.
+    pub fn stream_find_iter<R: Read>(&self, reader: R) -> StreamFindIter<'_, R> {
+        self.stream_find_iter_with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Returns an iterator over the matches in the stream like `stream_find_iter`, reading `chunk_size` bytes at a time.
+    pub fn stream_find_iter_with_chunk_size<R: Read>(
+        &self,
+        reader: R,
+        chunk_size: usize,
+    ) -> StreamFindIter<'_, R> {
+        StreamFindIter::new(self.linear_program(), reader, chunk_size)
+    }
+
+    /// Returns an iterator over the successive non-overlapping matches in the value, from left to right.
+    /// After an empty match the search continues one character later, so every position is tried once.
+    pub fn find_iter<'a>(&'a self, value: &'a str) -> impl Iterator<Item = MatchResult> + 'a {
+        let mut at = 0;
+        std::iter::from_fn(move || {
+            if at > value.len() {
+                return None;
+            }
+            let result = self.find_at(value, at)?;
+            at = if result.end > result.start {
+                result.end
+            } else {
+                result.end + value[result.end..].chars().next().map_or(1, char::len_utf8)
+            };
+            Some(result)
+        })
+    }
+
+    /// Returns an iterator over the text between the matches of the value, see `find_iter` for the matches found.
+    /// A match at the start or at the end of the value yields an empty string at that side.
+    pub fn split<'a>(&'a self, value: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let mut matches = self.find_iter(value);
+        let mut last = Some(0);
+        std::iter::from_fn(move || {
+            let start = last?;
+            match matches.next() {
+                Some(result) => {
+                    last = Some(result.end);
+                    Some(&value[start..result.start])
+                }
+                None => {
+                    last = None;
+                    Some(&value[start..])
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over at most `limit` pieces of the value split by the matches.
+    /// The last piece contains the rest of the value, without splitting it.
+    pub fn splitn<'a>(
+        &'a self,
+        value: &'a str,
+        limit: usize,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        let mut matches = self.find_iter(value);
+        let mut last = Some(0);
+        let mut count = 0;
+        std::iter::from_fn(move || {
+            let start = last?;
+            count += 1;
+            if count > limit {
+                last = None;
+                return None;
+            }
+            match matches.next().filter(|_| count < limit) {
+                Some(result) => {
+                    last = Some(result.end);
+                    Some(&value[start..result.start])
+                }
+                None => {
+                    last = None;
+                    Some(&value[start..])
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over the segments of the value, both the matches and the text between them, in order.
+    /// The matches are the ones of `find_iter`, so empty matches yield empty matched segments,
+    /// while the text between two adjacent matches is skipped when it is empty.
+    pub fn tokenize<'a>(&'a self, value: &'a str) -> impl Iterator<Item = Segment<'a>> + 'a {
+        let mut last = 0;
+        self.find_iter(value)
+            .map(Some)
+            .chain([None])
+            .flat_map(move |result| {
+                let (start, end) = result
+                    .as_ref()
+                    .map_or((value.len(), value.len()), MatchResult::range);
+                let unmatched = Segment {
+                    kind: SegmentKind::Unmatched,
+                    start: last,
+                    end: start,
+                    text: &value[last..start],
+                };
+                last = end;
+                let matched = result.map(|_| Segment {
+                    kind: SegmentKind::Matched,
+                    start,
+                    end,
+                    text: &value[start..end],
+                });
+                [
+                    Some(unmatched).filter(|segment| segment.start < segment.end),
+                    matched,
+                ]
+            })
+            .flatten()
+    }
+
+    /// Replaces the leftmost match in the value.
+    /// The replacement can be a template like `"$1-${name}"` or a closure that receives the `MatchResult`.
+    pub fn replace<R: Replacer>(&self, value: &str, replacer: R) -> String {
+        self.replacen(value, 1, replacer)
+    }
+
+    /// Replaces every non-overlapping match in the value.
+    /// The replacement can be a template like `"$1-${name}"` or a closure that receives the `MatchResult`.
+    pub fn replace_all<R: Replacer>(&self, value: &str, replacer: R) -> String {
+        self.replacen(value, 0, replacer)
+    }
+
+    /// Replaces the first `limit` non-overlapping matches in the value, or all of them if `limit` is 0.
+    pub fn replacen<R: Replacer>(&self, value: &str, limit: usize, mut replacer: R) -> String {
+        let mut replaced = String::with_capacity(value.len());
+        let mut last = 0;
+        for (count, result) in self.find_iter(value).enumerate() {
+            if limit > 0 && count == limit {
+                break;
+            }
+            replaced.push_str(&value[last..result.start]);
+            replacer.replace_append(&result, &mut replaced);
+            last = result.end;
+        }
+        replaced.push_str(&value[last..]);
+        replaced
+    }
+
+    /// Checks if the haystack may contain a match, looking only for the literals required by the expression.
+    /// Returns `false` when no part of the expression can match, so the haystack can be skipped entirely.
+    /// The haystack can be a single line or a whole buffer of lines.
+    pub fn is_candidate(&self, haystack: &[u8]) -> bool {
+        self.parts.iter().any(|part| part.is_candidate(haystack))
+    }
+
+    /// Returns the names of the named groups of the expression, in the order of their opening parenthesis.
+    pub fn capture_names(&self) -> Vec<&str> {
+        let mut names = vec![];
+        collect_capture_names(&self.parts, &mut names);
+        names
+    }
+
+    /// Returns the strings matched by the expression if every alternative is a plain literal.
+    /// Such expressions can be searched with an Aho-Corasick automaton instead of the regex engine.
+    pub fn literals(&self) -> Option<Vec<String>> {
+        self.parts.iter().map(RegexPart::as_literal).collect()
+    }
+}
+
+/// Returns the highest index of the capturing groups of the parts, 0 if they have none.
+fn group_count(parts: &[RegexPart]) -> usize {
+    parts
+        .iter()
+        .flat_map(|part| &part.states)
+        .map(|state| match &state.value {
+            RegexVal::Group { parts, index, .. } => index.unwrap_or(0).max(group_count(parts)),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Adds the offset to the index of every capturing group of the parts, which are compiled again by `from_parts`.
+fn renumber_groups(parts: Vec<RegexPart>, offset: usize) -> Vec<RegexPart> {
+    if offset == 0 {
+        return parts;
+    }
+    parts
+        .into_iter()
+        .map(|part| {
+            let states = part
+                .states
+                .into_iter()
+                .map(|state| match state.value {
+                    RegexVal::Group { parts, index, name } => RegexState {
+                        value: RegexVal::Group {
+                            parts: renumber_groups(parts, offset),
+                            index: index.map(|index| index + offset),
+                            name,
+                        },
+                        repetition: state.repetition,
+                    },
+                    _ => state,
+                })
+                .collect();
+            RegexPart::new(states, part.starts_with_caret, part.ends_with_dollar)
+        })
+        .collect()
+}
+
+fn collect_capture_names<'a>(parts: &'a [RegexPart], names: &mut Vec<&'a str>) {
+    for state in parts.iter().flat_map(|part| &part.states) {
+        if let RegexVal::Group { parts, name, .. } = &state.value {
+            if let Some(name) = name {
+                names.push(name.as_str());
+            }
+            collect_capture_names(parts, names);
+        }
+    }
+}
+
+/// The default maximum number of instructions of the programs compiled for an expression.
+pub const DEFAULT_SIZE_LIMIT: usize = 100_000;
+
+/// The deepest the groups of an expression can be nested, since parsing and compiling them recurse once per level.
+pub const MAX_NESTING_DEPTH: usize = 250;
+
+/// The options that change how the tokens of an expression are turned into values.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    /// The expression is matched against bytes, so `\xNN` escapes above `\x7f` are raw bytes instead of characters.
+    pub bytes: bool,
+    /// The `.` also matches the bytes that aren't part of a valid UTF-8 character, and newlines too.
+    pub dot_matches_any_byte: bool,
+    /// The letters match both their uppercase and lowercase forms.
+    pub case_insensitive: bool,
+    /// The `^` and `$` anchors also match after and before every newline, not only at the start and end of the value.
+    pub multiline: bool,
+    /// The `.` matches newlines too.
+    pub dot_matches_newline: bool,
+    /// The case insensitivity and the `[:alnum:]` and `[:space:]` classes apply to every Unicode character.
+    /// Otherwise, only ASCII characters are considered.
+    pub unicode: bool,
+    /// The maximum number of instructions of the programs compiled for the expression, all its alternatives together.
+    pub size_limit: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            bytes: false,
+            dot_matches_any_byte: false,
+            case_insensitive: false,
+            multiline: false,
+            dot_matches_newline: true,
+            unicode: true,
+            size_limit: DEFAULT_SIZE_LIMIT,
+        }
+    }
+}
+
+/// Parses the tokens of an expression into the alternatives of a regex, and of the groups inside it.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// The number of groups open at the current position.
+    depth: usize,
+    group_count: usize,
+    group_names: Vec<String>,
+    options: ParseOptions,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Parses alternatives separated by `|`, until the end of the expression or of the current group.
+    fn parse_alternatives(&mut self) -> Result<Vec<RegexPart>, RegexError> {
+        let mut parts = vec![self.parse_part()?];
+        while matches!(self.peek(), Some(Token::Alternation)) {
+            self.position += 1;
+            parts.push(self.parse_part()?);
+        }
+        Ok(parts)
+    }
+
+    /// Parses the states of a single alternative, until a `|`, the end of a group or the end of the expression.
+    fn parse_part(&mut self) -> Result<RegexPart, RegexError> {
+        let mut states: Vec<RegexState> = vec![];
+        let mut starts_with_caret = false;
+        let mut ends_with_dollar = false;
+        while let Some(token) = self.peek().cloned() {
+            if matches!(token, Token::Alternation | Token::GroupClose) {
+                break;
+            }
+            self.position += 1;
+            let state = match token {
+                Token::Literal(c) => parse_literal(c),
+                Token::Dot => parse_dot(&self.options),
+                Token::Byte(b) => parse_byte(b, self.options.bytes),
+                Token::Star => parse_star(&mut states, self.options.size_limit),
+                Token::Plus => parse_plus(&mut states, self.options.size_limit),
+                Token::Question => parse_question(&mut states, self.options.size_limit),
+                Token::Interval(content) => {
+                    parse_curly_bracket(&mut content.chars(), &mut states, self.options.size_limit)
+                }
+                Token::Bracket(expression) => parse_bracket(expression),
+                Token::Caret if self.options.multiline => {
+                    parse_line_anchor(&states, RegexVal::StartOfLine)
+                }
+                Token::Dollar if self.options.multiline => match self.peek() {
+                    None | Some(Token::Alternation) | Some(Token::GroupClose) => {
+                        parse_line_anchor(&[], RegexVal::EndOfLine)
+                    }
+                    _ => Err(RegexError::InvalidRegularExpression),
+                },
+                Token::Caret => parse_caret(&states, &mut starts_with_caret),
+                Token::Dollar => parse_dollar(self.peek(), &mut ends_with_dollar),
+                Token::Value(value) => parse_value(value),
+                Token::GroupOpen(kind) => self.parse_group(kind),
+                Token::Alternation | Token::GroupClose => Ok(None),
+            };
+            match state {
+                Ok(Some(s)) => states.push(RegexState {
+                    value: self.adjust_value(s.value),
+                    repetition: s.repetition,
+                }),
+                Ok(None) => (),
+                Err(err) => return Err(err),
+            }
+        }
+        new_part(
+            states,
+            starts_with_caret,
+            ends_with_dollar,
+            self.options.size_limit,
+        )
+    }
+
+    /// Applies the case insensitivity and the Unicode options to a value that matches a single character.
+    fn adjust_value(&self, value: RegexVal) -> RegexVal {
+        let unicode = self.options.unicode;
+        let value = match value {
+            RegexVal::Class(RegexClass::Alnum) if !unicode => {
+                ascii_value(char::is_ascii_alphanumeric)
+            }
+            RegexVal::Class(RegexClass::Space) if !unicode => {
+                ascii_value(char::is_ascii_whitespace)
+            }
+            value => value,
+        };
+        if !self.options.case_insensitive {
+            return value;
+        }
+        match value {
+            RegexVal::Literal(c) => {
+                let chars = case_variants(c, unicode);
+                if chars.len() == 1 {
+                    RegexVal::Literal(c)
+                } else {
+                    RegexVal::BracketExpression {
+                        chars,
+                        is_negated: false,
+                    }
+                }
+            }
+            RegexVal::BracketExpression { chars, is_negated } => RegexVal::BracketExpression {
+                chars: chars
+                    .into_iter()
+                    .flat_map(|c| case_variants(c, unicode))
+                    .collect(),
+                is_negated,
+            },
+            RegexVal::Class(RegexClass::Upper | RegexClass::Lower) => {
+                RegexVal::Class(RegexClass::Alpha)
+            }
+            value => value,
+        }
+    }
+
+    /// Tries to parse a group, after its opening parenthesis.
+    /// The capturing groups are numbered in the order of their opening parenthesis, and their names must be unique.
+    /// Returns a `NestingLimitExceeded` error if the group is nested deeper than `MAX_NESTING_DEPTH`.
+    fn parse_group(&mut self, kind: GroupKind) -> Result<Option<RegexState>, RegexError> {
+        let (index, name) = match kind {
+            GroupKind::Capturing => (Some(self.next_group_index()), None),
+            GroupKind::NonCapturing => (None, None),
+            GroupKind::Named(name) => {
+                if self.group_names.contains(&name) {
+                    return Err(RegexError::InvalidGroupName);
+                }
+                self.group_names.push(name.clone());
+                (Some(self.next_group_index()), Some(name))
+            }
+        };
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(RegexError::NestingLimitExceeded);
+        }
+        self.depth += 1;
+        let parts = self.parse_alternatives()?;
+        self.depth -= 1;
+        if !matches!(self.peek(), Some(Token::GroupClose)) {
+            return Err(RegexError::UnmatchedParenthesis);
+        }
+        self.position += 1;
+        let value = RegexVal::Group { parts, index, name };
+        if value.nesting_depth() > MAX_NESTING_DEPTH {
+            return Err(RegexError::NestingLimitExceeded);
+        }
+        Ok(Some(RegexState {
+            value,
+            repetition: RegexRep::Exact(1),
+        }))
+    }
+
+    fn next_group_index(&mut self) -> usize {
+        self.group_count += 1;
+        self.group_count
     }
 }
 
@@ -103,117 +792,235 @@ fn parse_literal(c: char) -> Result<Option<RegexState>, RegexError> {
     }))
 }
 
+/// Tries to parse a value already decided by the tokenizer, like a class escape or an anchor.
+/// # Arguments
+/// * `value` - The value to parse.
+/// # Returns
+/// An optional `RegexState` in a Result representing the parsed value.
+fn parse_value(value: RegexVal) -> Result<Option<RegexState>, RegexError> {
+    Ok(Some(RegexState {
+        value,
+        repetition: RegexRep::Exact(1),
+    }))
+}
+
+/// Creates a part from its states, checking that its compiled program isn't bigger than the size limit.
+fn new_part(
+    states: Vec<RegexState>,
+    starts_with_caret: bool,
+    ends_with_dollar: bool,
+    size_limit: usize,
+) -> Result<RegexPart, RegexError> {
+    if nfa::part_size(&states, starts_with_caret, ends_with_dollar) > size_limit {
+        return Err(RegexError::SizeLimitExceeded);
+    }
+    Ok(RegexPart::new(states, starts_with_caret, ends_with_dollar))
+}
+
+/// Returns the character with its other cases, only for ASCII letters if `unicode` is `false`.
+/// Only the case mappings to a single character are considered.
+fn case_variants(c: char, unicode: bool) -> Vec<char> {
+    let mut variants = vec![c];
+    if !unicode && !c.is_ascii() {
+        return variants;
+    }
+    let mappings: [Vec<char>; 2] = [c.to_lowercase().collect(), c.to_uppercase().collect()];
+    for mapping in mappings {
+        if let [other] = mapping[..] {
+            if !variants.contains(&other) {
+                variants.push(other);
+            }
+        }
+    }
+    variants
+}
+
+/// Returns a value matching the ASCII characters that satisfy the predicate.
+fn ascii_value(predicate: fn(&char) -> bool) -> RegexVal {
+    RegexVal::BracketExpression {
+        chars: (0..=127u8).map(char::from).filter(predicate).collect(),
+        is_negated: false,
+    }
+}
+
 /// Tries to parse a dot in a expression.
 /// # Arguments
-/// * `c` - A character representing the dot to parse.
+/// * `options` - The options deciding if the dot matches newlines and invalid bytes.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed dot if it was successful.
-fn parse_dot() -> Result<Option<RegexState>, RegexError> {
+fn parse_dot(options: &ParseOptions) -> Result<Option<RegexState>, RegexError> {
+    let value = if options.dot_matches_any_byte {
+        RegexVal::AnyByte
+    } else if options.dot_matches_newline {
+        RegexVal::Wildcard
+    } else {
+        RegexVal::BracketExpression {
+            chars: vec!['\n'],
+            is_negated: true,
+        }
+    };
     Ok(Some(RegexState {
-        value: RegexVal::Wildcard,
+        value,
         repetition: RegexRep::Exact(1),
     }))
 }
 
-/// Tries to parse a star in a expression.
+/// Tries to parse a `\xNN` escape in a expression.
 /// # Arguments
-/// * `states` - A mutable reference to the vector of `RegexState` objects.
+/// * `b` - The value of the escape.
+/// * `bytes` - If the expression is matched against bytes, where the values above `0x7f` are raw bytes.
 /// # Returns
-/// An optional `RegexState` in a Result representing the parsed star if it was successful.
-fn parse_star(states: &mut [RegexState]) -> Result<Option<RegexState>, RegexError> {
-    if let Some(last) = states.last_mut() {
-        last.repetition = RegexRep::Any;
-        Ok(None)
+/// An optional `RegexState` in a Result representing the parsed escape.
+fn parse_byte(b: u8, bytes: bool) -> Result<Option<RegexState>, RegexError> {
+    let value = if bytes && !b.is_ascii() {
+        RegexVal::Byte(b)
     } else {
-        Err(RegexError::InvalidRegularExpression)
-    }
+        RegexVal::Literal(char::from(b))
+    };
+    Ok(Some(RegexState {
+        value,
+        repetition: RegexRep::Exact(1),
+    }))
 }
 
-/// Tries to parse a backslash in a expression.
+/// Tries to parse a star in a expression.
 /// # Arguments
-/// * `chars_iter` - A mutable reference to the character iterator.
+/// * `states` - A mutable reference to the vector of `RegexState` objects.
+/// * `size_limit` - The size limit of the programs, checked if the star repeats another repetition.
 /// # Returns
-/// An optional `RegexState`  in a Result representing the parsed backslash if it was successful.
-fn parse_backslash(chars_iter: &mut std::str::Chars<'_>) -> Result<Option<RegexState>, RegexError> {
-    match chars_iter.next() {
-        Some(literal) => Ok(Some(RegexState {
-            value: RegexVal::Literal(literal),
-            repetition: RegexRep::Exact(1),
-        })),
-        None => Err(RegexError::InvalidRegularExpression),
-    }
+/// An optional `RegexState` in a Result representing the parsed star if it was successful.
+fn parse_star(
+    states: &mut [RegexState],
+    size_limit: usize,
+) -> Result<Option<RegexState>, RegexError> {
+    repeat_last(states, RegexRep::Any, size_limit)
 }
 
 /// Tries to parse a question mark in a expression.
 /// # Arguments
 /// * `states` - A mutable reference to the vector of `RegexState` objects.
+/// * `size_limit` - The size limit of the programs, checked if the question mark repeats another repetition.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed question mark if it was successful.
-fn parse_question(states: &mut [RegexState]) -> Result<Option<RegexState>, RegexError> {
-    if let Some(last) = states.last_mut() {
-        last.repetition = RegexRep::Range {
-            min: Some(0),
-            max: Some(1),
-        };
-        Ok(None)
-    } else {
-        Err(RegexError::InvalidRegularExpression)
-    }
+fn parse_question(
+    states: &mut [RegexState],
+    size_limit: usize,
+) -> Result<Option<RegexState>, RegexError> {
+    let repetition = RegexRep::Range {
+        min: Some(0),
+        max: Some(1),
+    };
+    repeat_last(states, repetition, size_limit)
 }
 
 /// Tries to parse a plus in a expression.
 /// # Arguments
 /// * `states` - A mutable reference to the vector of `RegexState` objects.
+/// * `size_limit` - The size limit of the programs, checked if the plus repeats another repetition.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed plus if it was successful.
-fn parse_plus(states: &mut [RegexState]) -> Result<Option<RegexState>, RegexError> {
-    if let Some(last) = states.last_mut() {
-        last.repetition = RegexRep::Range {
-            min: Some(1),
-            max: None,
+fn parse_plus(
+    states: &mut [RegexState],
+    size_limit: usize,
+) -> Result<Option<RegexState>, RegexError> {
+    let repetition = RegexRep::Range {
+        min: Some(1),
+        max: None,
+    };
+    repeat_last(states, repetition, size_limit)
+}
+
+/// Applies the repetition to the last state.
+/// If the state is already repeated, like in `a{2}*`, it is wrapped in a group so both repetitions apply,
+/// which counts as a nested group for the nesting limit.
+fn repeat_last(
+    states: &mut [RegexState],
+    repetition: RegexRep,
+    size_limit: usize,
+) -> Result<Option<RegexState>, RegexError> {
+    let Some(last) = states.last_mut() else {
+        return Err(RegexError::InvalidRegularExpression);
+    };
+    if !matches!(last.repetition, RegexRep::Exact(1)) {
+        let repeated = last.clone();
+        last.value = RegexVal::Group {
+            parts: vec![new_part(vec![repeated], false, false, size_limit)?],
+            index: None,
+            name: None,
         };
-        Ok(None)
-    } else {
-        Err(RegexError::InvalidRegularExpression)
+        if last.value.nesting_depth() > MAX_NESTING_DEPTH {
+            return Err(RegexError::NestingLimitExceeded);
+        }
+    }
+    last.repetition = repetition;
+    Ok(None)
+}
+
+/// Tries to parse an anchor that matches at line boundaries in multiline mode.
+/// Like the anchors of the whole value, the start of a line is only valid at the start of an alternative.
+/// # Arguments
+/// * `states` - The states already parsed in the alternative.
+/// * `anchor` - The value of the anchor.
+/// # Returns
+/// An optional `RegexState` in a Result representing the parsed anchor if it was successful.
+fn parse_line_anchor(
+    states: &[RegexState],
+    anchor: RegexVal,
+) -> Result<Option<RegexState>, RegexError> {
+    if !states.is_empty() {
+        return Err(RegexError::InvalidRegularExpression);
     }
+    Ok(Some(RegexState {
+        value: anchor,
+        repetition: RegexRep::Exact(1),
+    }))
 }
 
-/// Tries to parse a caret in a expression.
+/// Tries to parse a caret in a expression, which is only valid at the start of an alternative.
 /// # Arguments
-/// * `expr` - A string slice representing the expression to parse.
+/// * `states` - The states already parsed in the alternative.
+/// * `starts_with_caret` - A mutable reference to the flag of the alternative, set if the caret is valid.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed caret if it was successful.
-fn parse_caret(expr: &str) -> Result<Option<RegexState>, RegexError> {
-    if expr.starts_with('^') {
+fn parse_caret(
+    states: &[RegexState],
+    starts_with_caret: &mut bool,
+) -> Result<Option<RegexState>, RegexError> {
+    if states.is_empty() && !*starts_with_caret {
+        *starts_with_caret = true;
         Ok(None)
     } else {
         Err(RegexError::InvalidRegularExpression)
     }
 }
 
-/// Tries to parse a dollar in a expression.
+/// Tries to parse a dollar in a expression, which is only valid at the end of an alternative.
 /// # Arguments
-/// * `chars_iter` - A mutable reference to the character iterator.
+/// * `next` - The token after the dollar.
+/// * `ends_with_dollar` - A mutable reference to the flag of the alternative, set if the dollar is valid.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed dollar if it was successful.
-fn parse_dollar(chars_iter: &mut std::str::Chars<'_>) -> Result<Option<RegexState>, RegexError> {
-    if chars_iter.next().is_none() {
-        Ok(None)
-    } else {
-        Err(RegexError::InvalidRegularExpression)
+fn parse_dollar(
+    next: Option<&Token>,
+    ends_with_dollar: &mut bool,
+) -> Result<Option<RegexState>, RegexError> {
+    match next {
+        None | Some(Token::Alternation) | Some(Token::GroupClose) => {
+            *ends_with_dollar = true;
+            Ok(None)
+        }
+        _ => Err(RegexError::InvalidRegularExpression),
     }
 }
 
 /// Tries to parse a bracket in a expression.
 /// # Arguments
-/// * `chars_iter` - A mutable reference to the character iterator.
+/// * `expression` - The elements inside the bracket.
 /// # Returns
 /// An optional `RegexState` in a Result representing the parsed bracket if it was successful.
-fn parse_bracket(chars_iter: &mut std::str::Chars<'_>) -> Result<Option<RegexState>, RegexError> {
-    let expression = get_expression_inside_bracket(chars_iter);
-    let class_name = expression[1..].iter().collect::<String>();
-    if RegexClass::is_character_class(&class_name) {
-        chars_iter.next();
+fn parse_bracket(expression: Vec<char>) -> Result<Option<RegexState>, RegexError> {
+    if syntax::is_character_class(&expression) {
+        let class_name = expression[1..].iter().collect::<String>();
         match parse_character_class(&class_name) {
             Ok(result) => Ok(Some(result)),
             Err(_) => Err(RegexError::InvalidRegularExpression),
@@ -232,14 +1039,10 @@ fn parse_bracket(chars_iter: &mut std::str::Chars<'_>) -> Result<Option<RegexSta
 fn parse_curly_bracket(
     chars_iter: &mut std::str::Chars<'_>,
     states: &mut [RegexState],
+    size_limit: usize,
 ) -> Result<Option<RegexState>, RegexError> {
     let repetition = parse_range_repetition(chars_iter)?;
-    if let Some(last) = states.last_mut() {
-        last.repetition = repetition;
-    } else {
-        return Err(RegexError::InvalidRegularExpression);
-    }
-    Ok(None)
+    repeat_last(states, repetition, size_limit)
 }
 
 /// Tries to parse a bracket expression in a expression.
@@ -253,27 +1056,7 @@ fn parse_curly_bracket(
 ///
 /// An optional `RegexState` representing the parsed bracket expression if it was successful.
 fn parse_bracket_expression(expression: Vec<char>) -> Result<Option<RegexState>, RegexError> {
-    let mut is_negated = false;
-    let mut expression = expression;
-    let mut chars: Vec<char> = vec![];
-    if expression[0] == '^' {
-        is_negated = true;
-        expression.remove(0);
-    }
-
-    if expression.len() == 3 && expression[1] == '-' {
-        if expression[0] > expression[2] {
-            return Err(RegexError::InvalidBracketRange);
-        }
-        let start = expression[0] as u8;
-        let end = expression[2] as u8;
-        for c in start..=end {
-            chars.push(c as char);
-        }
-    } else {
-        chars = expression;
-    }
-
+    let BracketExpression { chars, is_negated } = BracketExpression::parse(&expression)?;
     Ok(Some(RegexState {
         value: RegexVal::BracketExpression { chars, is_negated },
         repetition: RegexRep::Exact(1),
@@ -299,6 +1082,9 @@ fn parse_character_class(class_name: &str) -> Result<RegexState, RegexError> {
 }
 
 /// Tries to parse a range repetition in a expression.
+/// The bounds that don't fit in a `usize` are rejected with a `SizeLimitExceeded` error, like any repetition too
+/// big to compile, and the ranges without bounds, like `{,}`, or with a minimum bigger than the maximum,
+/// like `{2,1}`, with an `InvalidRegularExpression` error.
 ///
 /// # Arguments
 ///
@@ -309,43 +1095,43 @@ fn parse_character_class(class_name: &str) -> Result<RegexState, RegexError> {
 /// A `Result` containing the parsed `RegexRep` representing the repetition range if it was successful,
 fn parse_range_repetition(chars_iter: &mut std::str::Chars<'_>) -> Result<RegexRep, RegexError> {
     let mut min = None;
-    let mut max = None;
-    let mut parameters = Vec::new();
+    let mut parameters = String::new();
+    let mut has_comma = false;
 
     for c in chars_iter.by_ref() {
         match c {
             '}' => break,
-            ',' => {
-                if !parameters.is_empty() {
-                    min = parameters.iter().collect::<String>().parse().ok();
-                    parameters.clear();
-                }
+            ',' if !has_comma => {
+                has_comma = true;
+                min = parse_bound(&parameters)?;
+                parameters.clear();
             }
             _ if c.is_ascii_digit() => parameters.push(c),
             _ => return Err(RegexError::InvalidRegularExpression),
         }
     }
+    let max = parse_bound(&parameters)?;
 
-    if !parameters.is_empty() {
-        max = parameters.iter().collect::<String>().parse().ok();
+    match (has_comma, min, max) {
+        (false, _, Some(n)) => Ok(RegexRep::Exact(n)),
+        (_, None, None) => Err(RegexError::InvalidRegularExpression),
+        (true, Some(min), Some(max)) if min > max => Err(RegexError::InvalidRegularExpression),
+        _ => Ok(RegexRep::Range { min, max }),
     }
-
-    Ok(RegexRep::Range { min, max })
 }
 
-/// Collect the elements inside an expression that starts with a bracket and return them in a vector
-fn get_expression_inside_bracket(chars_iter: &mut std::str::Chars<'_>) -> Vec<char> {
-    let mut expression = vec![];
-    for c in chars_iter.by_ref() {
-        if c == ']' {
-            break;
-        }
-        expression.push(c);
+/// Parses a bound of a range repetition, `None` if it is empty.
+fn parse_bound(digits: &str) -> Result<Option<usize>, RegexError> {
+    if digits.is_empty() {
+        return Ok(None);
     }
-    expression
+    digits
+        .parse()
+        .map(Some)
+        .map_err(|_| RegexError::SizeLimitExceeded)
 }
 
-/* 
+/*
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -782,4 +1568,4 @@ mod tests {
         );
     }
 }
-*/
\ No newline at end of file
+*/
@@ -0,0 +1,89 @@
+use crate::error::RegexError;
+use crate::regex::{Engine, ParseOptions, Regex, DEFAULT_MATCH_LIMIT};
+use crate::syntax::Syntax;
+
+/// Builder to configure a `Regex` before compiling it.
+#[derive(Debug, Clone)]
+pub struct RegexBuilder {
+    syntax: Syntax,
+    options: ParseOptions,
+    engine: Engine,
+    match_limit: Option<usize>,
+}
+
+impl Default for RegexBuilder {
+    fn default() -> Self {
+        RegexBuilder::new()
+    }
+}
+
+impl RegexBuilder {
+    /// Creates a builder for case sensitive extended regular expressions, with the backtracking engine,
+    /// the default size and match limits, and where `^` and `$` only match at the start and end of the value.
+    pub fn new() -> RegexBuilder {
+        RegexBuilder {
+            syntax: Syntax::Extended,
+            options: ParseOptions::default(),
+            engine: Engine::Backtracking,
+            match_limit: Some(DEFAULT_MATCH_LIMIT),
+        }
+    }
+
+    /// Sets the dialect the expressions are written in.
+    pub fn syntax(&mut self, syntax: Syntax) -> &mut RegexBuilder {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Enables matching letters without regard to their case.
+    pub fn case_insensitive(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.options.case_insensitive = yes;
+        self
+    }
+
+    /// Enables matching `^` and `$` after and before every newline too.
+    pub fn multiline(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.options.multiline = yes;
+        self
+    }
+
+    /// Sets whether `.` matches newlines, which it does by default.
+    pub fn dot_matches_newline(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.options.dot_matches_newline = yes;
+        self
+    }
+
+    /// Sets whether the case insensitivity and the `[:alnum:]` and `[:space:]` classes apply to every Unicode
+    /// character, which they do by default, or only to ASCII characters.
+    pub fn unicode(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.options.unicode = yes;
+        self
+    }
+
+    /// Sets the engine used to search the matches.
+    pub fn engine(&mut self, engine: Engine) -> &mut RegexBuilder {
+        self.engine = engine;
+        self
+    }
+
+    /// Sets the maximum number of instructions of the program compiled for the expression, all its alternatives together.
+    /// Bigger expressions, like `a{1000}{1000}`, are rejected with a `SizeLimitExceeded` error.
+    pub fn size_limit(&mut self, limit: usize) -> &mut RegexBuilder {
+        self.options.size_limit = limit;
+        self
+    }
+
+    /// Sets the number of steps the backtracking engine can take in a single search, `None` removes the limit.
+    pub fn match_limit(&mut self, limit: Option<usize>) -> &mut RegexBuilder {
+        self.match_limit = limit;
+        self
+    }
+
+    /// Compiles the expression with the options of the builder.
+    pub fn build(&self, expression: &str) -> Result<Regex, RegexError> {
+        let mut regex = Regex::with_options(expression, self.syntax, self.options)?;
+        regex.engine = self.engine;
+        regex.set_match_limit(self.match_limit);
+        Ok(regex)
+    }
+}
@@ -1,172 +1,250 @@
 use crate::error::RegexError;
 use crate::evaluated_state::EvaluatedStep;
-use crate::match_result::MatchResult;
+use crate::match_result::{Capture, MatchResult};
+use crate::nfa::{Inst, Nfa};
+use crate::prefilter::Finder;
 use crate::regex_rep::RegexRep;
 use crate::regex_state::RegexState;
-use std::collections::VecDeque;
+use crate::regex_value::RegexVal;
 
-/// A RegexPart is a regular expression, it is composed of a list of states and a boolean that indicates if the expression ends with a dollar sign.
+/// The positions stored in the slots of a program while it runs, `None` for the slots not reached.
+pub type Slots = Vec<Option<usize>>;
+
+/// The memory a program works with while it runs, kept from one run to the next,
+/// so the searches that try many start positions, or many lines, allocate it only once.
+#[derive(Debug, Default)]
+pub struct Backtracker {
+    slots: Slots,
+    stack: Vec<EvaluatedStep>,
+}
+
+/// A RegexPart is a regular expression, it is composed of a list of states and the anchors at its start and end.
 /// The idea of this struct is to be able to partition a regular expression in different parts.
 /// This is useful for regular expressions that contain the OR operator, because we can evaluate each part of the expression separately.
 /// If the regular expression does not contain the OR operator, the RegexPart will contain one regular expression, as a RegexPart.
-#[derive(Debug)]
+/// The alternatives inside a group are also represented as RegexParts.
+#[derive(Debug, Clone)]
 pub struct RegexPart {
     pub states: Vec<RegexState>,
+    pub starts_with_caret: bool,
     pub ends_with_dollar: bool,
+    /// The longest literal that every match of this part contains, used to skip values without it.
+    pub literal: Option<Finder>,
+    /// The states compiled into an automaton, which is run by the backtracking matcher.
+    /// Only the parts at the top level of a regex are compiled, see `Regex::from_parts`, since the parts
+    /// inside a group are compiled into the program of the part that contains it. Their program stays empty.
+    pub program: Nfa,
 }
 
 impl RegexPart {
-    /// Tries to match a single expression with the regular expression part.
-    /// It returns a boolean indicating if the expression matches the regular expression part.
-    pub fn match_single_expression(&self, value: &str) -> Result<Option<MatchResult>, RegexError> {
-        if !value.is_ascii() {
-            return Err(RegexError::NonAsciiInput);
+    /// Creates a new `RegexPart`, extracting the literal that is required for a match.
+    /// Its states aren't compiled yet, see `compile`.
+    pub fn new(
+        states: Vec<RegexState>,
+        starts_with_caret: bool,
+        ends_with_dollar: bool,
+    ) -> RegexPart {
+        let literal = required_literal(&states).map(|literal| Finder::new(literal.as_bytes()));
+        RegexPart {
+            states,
+            starts_with_caret,
+            ends_with_dollar,
+            literal,
+            program: Nfa::default(),
         }
+    }
 
-        let mut queue = VecDeque::from(self.states.clone());
-        let mut stack = Vec::new();
-        let mut index = 0;
-        let mut match_start = index;
+    /// Compiles the states of the part into its program, unless it was already compiled.
+    pub fn compile(&mut self) {
+        if self.program.insts.is_empty() {
+            self.program = Nfa::compile_part(self);
+        }
+    }
 
-        'states: while let Some(state) = queue.pop_front() {
-            match state.repetition {
-                RegexRep::Exact(n) => {
-                    let mut match_size = 0;
-                    for _ in 0..n {
-                        let s = state.value.matches(&value[index..]);
-                        if s == 0 {
-                            match backtrack(state, &mut stack, &mut queue) {
-                                Some(size) => {
-                                    index -= size;
-                                    match_start = index;
-                                    continue 'states;
-                                }
-                                None => return Ok(None),
-                            }
-                        } else {
-                            match_size += s;
-                            index += s;
-                        }
-                    }
-                    stack.push(EvaluatedStep {
-                        state,
-                        match_size,
-                        backtrackable: false,
-                    })
-                }
-                RegexRep::Any => {
-                    handle_any_repetition(&mut index, &state, value, &mut stack);
-                }
-                RegexRep::Range { min, max } => {
-                    let (match_size, count) = get_count_size(&state, value, &mut index, max);
-                    if let Some(m) = min {
-                        if count < m {
-                            match backtrack(state, &mut stack, &mut queue) {
-                                Some(size) => {
-                                    index -= size;
-                                    continue 'states;
-                                }
-                                None => return Ok(None),
-                            }
-                        }
-                        stack.push(EvaluatedStep {
-                            state,
-                            match_size,
-                            backtrackable: count > m,
-                        })
-                    }
+    /// Returns `false` if the value lacks the literal required by this part, so it can't match.
+    pub fn is_candidate(&self, value: &[u8]) -> bool {
+        match &self.literal {
+            Some(finder) => finder.find(value).is_some(),
+            None => true,
+        }
+    }
+
+    /// Returns the string this part matches if it is a plain literal, with no anchors nor special values.
+    pub fn as_literal(&self) -> Option<String> {
+        if self.starts_with_caret || self.ends_with_dollar {
+            return None;
+        }
+        let mut literal = String::new();
+        for state in &self.states {
+            match (&state.value, state.repetition) {
+                (RegexVal::Literal(c), RegexRep::Exact(n)) => {
+                    literal.extend(std::iter::repeat_n(*c, n))
                 }
+                _ => return None,
             }
         }
+        Some(literal)
+    }
+
+    /// Returns `true` if the part can match the empty string.
+    pub fn can_match_empty(&self) -> bool {
+        self.states
+            .iter()
+            .all(|state| state.repetition.bounds().0 == 0 || state.value.can_match_empty())
+    }
 
-        if self.ends_with_dollar {
-            if index == value.len() {
-                let matched = value[match_start..index].to_string();
-                Ok(Some(MatchResult {
-                    start: match_start,
-                    end: index,
-                    matched,
-                }))
-            } else {
-                Ok(None)
+    /// Tries to match a single expression with the regular expression part.
+    /// The match is searched starting at every position of the value, from left to right,
+    /// and the first one found is returned.
+    pub fn match_single_expression(&self, value: &str) -> Result<Option<MatchResult>, RegexError> {
+        let mut steps_left = usize::MAX;
+        let mut backtracker = Backtracker::default();
+        for (start, _) in value.char_indices().chain([(value.len(), ' ')]) {
+            if self.starts_with_caret && start > 0 {
+                break;
+            }
+            if let Some((end, slots)) =
+                self.run(value.as_bytes(), start, &mut steps_left, &mut backtracker)?
+            {
+                return Ok(Some(
+                    MatchResult::new(start, end, value[start..end].to_string())
+                        .with_captures(self.captures(value, &slots)),
+                ));
             }
-        } else {
-            let matched = value[match_start..index].to_string();
-            Ok(Some(MatchResult {
-                start: match_start,
-                end: index,
-                matched,
-            }))
         }
+        Ok(None)
     }
-}
 
-fn handle_any_repetition(
-    index: &mut usize,
-    state: &RegexState,
-    value: &str,
-    stack: &mut Vec<EvaluatedStep>,
-) {
-    let mut keep_matching = true;
-    while keep_matching {
-        let match_size = state.value.matches(&value[*index..]);
-        if match_size != 0 {
-            *index += match_size;
-            stack.push(EvaluatedStep {
-                state: state.clone(),
-                match_size,
-                backtrackable: true,
-            });
-        } else {
-            keep_matching = false;
-        }
+    /// Returns the text captured by the groups of the part, given the slots filled by a run of its program.
+    pub fn captures(&self, value: &str, slots: &[Option<usize>]) -> Vec<Capture> {
+        self.program
+            .groups
+            .iter()
+            .filter_map(|group| match (slots[group.start], slots[group.end]) {
+                (Some(group_start), Some(group_end)) => Some(Capture {
+                    index: group.index,
+                    name: group.name.clone(),
+                    start: group_start,
+                    end: group_end,
+                    matched: value[group_start..group_end].to_string(),
+                }),
+                _ => None,
+            })
+            .collect()
     }
-}
 
-fn get_count_size(
-    state: &RegexState,
-    value: &str,
-    index: &mut usize,
-    max: Option<usize>,
-) -> (usize, usize) {
-    let mut match_size = 0;
-    let mut count = 0;
-    loop {
-        let s = state.value.matches(&value[*index..]);
-        if s == 0 {
-            break;
-        }
-        match_size += s;
-        *index += s;
-        count += 1;
-        if let Some(m) = max {
-            if count >= m {
-                break;
+    /// Runs the program starting at the given position, returning the end of the match and the content of the slots.
+    /// The part must be compiled, like the parts of a regex are. The slots and the evaluated steps are kept
+    /// in the backtracker, and only copied out when the program matches.
+    ///
+    /// The program is run following the preferred way at every split, and the other way is saved
+    /// as an evaluated step. When a value doesn't match, the last evaluated step is resumed.
+    /// Every instruction run takes one of the `steps_left`, and running out of them returns
+    /// a `MatchLimitExceeded` error, so patterns that backtrack too much can't run forever.
+    pub fn run(
+        &self,
+        value: &[u8],
+        start: usize,
+        steps_left: &mut usize,
+        backtracker: &mut Backtracker,
+    ) -> Result<Option<(usize, Slots)>, RegexError> {
+        let insts = &self.program.insts;
+        let Backtracker { slots, stack } = backtracker;
+        slots.clear();
+        slots.resize(self.program.slot_count, None);
+        stack.clear();
+        stack.push(EvaluatedStep::Resume {
+            pc: self.program.starts[0],
+            position: start,
+        });
+
+        while let Some(step) = stack.pop() {
+            let (mut pc, mut index) = match step {
+                EvaluatedStep::Resume { pc, position } => (pc, position),
+                EvaluatedStep::Restore { slot, position } => {
+                    slots[slot] = position;
+                    continue;
+                }
+            };
+            loop {
+                if *steps_left == 0 {
+                    return Err(RegexError::MatchLimitExceeded);
+                }
+                *steps_left -= 1;
+                match &insts[pc] {
+                    Inst::Value(regex_value) => {
+                        let size = regex_value.matches(&value[index..]);
+                        if size == 0 {
+                            break;
+                        }
+                        index += size;
+                        pc += 1;
+                    }
+                    Inst::Split(first, second) => {
+                        stack.push(EvaluatedStep::Resume {
+                            pc: *second,
+                            position: index,
+                        });
+                        pc = *first;
+                    }
+                    Inst::Jump(target) => pc = *target,
+                    Inst::AssertStart if index == 0 => pc += 1,
+                    Inst::AssertEnd if index == value.len() => pc += 1,
+                    Inst::AssertLineStart if index == 0 || value[index - 1] == b'\n' => pc += 1,
+                    Inst::AssertLineEnd if index == value.len() || value[index] == b'\n' => pc += 1,
+                    Inst::AssertStart
+                    | Inst::AssertEnd
+                    | Inst::AssertLineStart
+                    | Inst::AssertLineEnd => break,
+                    Inst::Mark(slot) => {
+                        stack.push(EvaluatedStep::Restore {
+                            slot: *slot,
+                            position: slots[*slot],
+                        });
+                        slots[*slot] = Some(index);
+                        pc += 1;
+                    }
+                    Inst::Progress(slot) => {
+                        if slots[*slot] == Some(index) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Match(_) => return Ok(Some((index, slots.clone()))),
+                }
             }
         }
+        Ok(None)
     }
-    (match_size, count)
 }
 
-/// This function is used to backtrack when a match is not found.
-/// It returns the size of the backtrack.
-/// The function receives the current state, a mutable reference to the evaluated steps and a mutable reference to the next states.
-/// The function pops the evaluated steps until it finds a backtrackable step, then it returns the size of the backtrack.
-fn backtrack(
-    current: RegexState,
-    evaluated: &mut Vec<EvaluatedStep>,
-    next: &mut VecDeque<RegexState>,
-) -> Option<usize> {
-    let mut backtrack_size = 0;
-
-    next.push_front(current);
-    while let Some(e) = evaluated.pop() {
-        backtrack_size += e.match_size;
-        if e.backtrackable {
-            return Some(backtrack_size);
-        } else {
-            next.push_front(e.state);
+/// Returns the longest run of consecutive literal states that must appear in every match.
+/// Literals with an optional or variable repetition end the current run.
+fn required_literal(states: &[RegexState]) -> Option<String> {
+    let mut longest = String::new();
+    let mut current = String::new();
+    for state in states {
+        match (&state.value, state.repetition) {
+            (RegexVal::Literal(c), RegexRep::Exact(n)) => {
+                current.extend(std::iter::repeat_n(*c, n));
+                continue;
+            }
+            (RegexVal::Literal(c), RegexRep::Range { min: Some(m), .. }) => {
+                current.extend(std::iter::repeat_n(*c, m));
+            }
+            _ => (),
+        }
+        if current.len() > longest.len() {
+            longest = std::mem::take(&mut current);
         }
+        current.clear();
+    }
+    if current.len() > longest.len() {
+        longest = current;
+    }
+    if longest.is_empty() {
+        None
+    } else {
+        Some(longest)
     }
-    None
 }
@@ -11,3 +11,14 @@ pub enum RegexRep {
         max: Option<usize>,
     },
 }
+
+impl RegexRep {
+    /// Returns the minimum and maximum number of repetitions, the maximum is `None` if it is unbounded.
+    pub fn bounds(&self) -> (usize, Option<usize>) {
+        match *self {
+            RegexRep::Exact(n) => (n, Some(n)),
+            RegexRep::Any => (0, None),
+            RegexRep::Range { min, max } => (min.unwrap_or(0), max),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::RegexError;
+use crate::match_result::MatchResult;
+use crate::nfa::Nfa;
+use crate::pike_vm;
+use crate::regex::Regex;
+
+/// A set of regular expressions compiled into a single automaton.
+/// Matching a value against the set runs every pattern in one pass over the value,
+/// instead of running each regex separately.
+#[derive(Debug)]
+pub struct RegexSet {
+    patterns: Vec<String>,
+    nfa: Nfa,
+}
+
+impl RegexSet {
+    /// Creates a new `RegexSet` from the given expressions.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The regular expressions of the set, each one is identified by its index.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `RegexSet` if every expression is valid, or the error of the first invalid one.
+    pub fn new<I, S>(patterns: I) -> Result<Self, RegexError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns: Vec<String> = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_string())
+            .collect();
+        let regexes = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<Vec<Regex>, RegexError>>()?;
+        let nfa = Nfa::compile(&regexes);
+        Ok(RegexSet { patterns, nfa })
+    }
+
+    /// Returns the expressions of the set, in the order they were given.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Returns the number of expressions in the set.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns `true` if the set has no expressions.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns `true` if any expression of the set matches the value.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.matches(value).matched_any()
+    }
+
+    /// Returns which expressions of the set match the value.
+    /// The search of each expression stops at its first match, so this is cheaper than `first_matches`.
+    pub fn matches(&self, value: &str) -> SetMatches {
+        let mut set_matches = SetMatches::new(self.len());
+        for (index, found) in pike_vm::search(&self.nfa, value.as_bytes(), true)
+            .iter()
+            .enumerate()
+        {
+            if found.is_some() {
+                set_matches.insert(index);
+            }
+        }
+        set_matches
+    }
+
+    /// Returns the leftmost match of every expression of the set, or `None` for the ones that don't match.
+    /// The result has one element per expression, in the same order as the expressions.
+    pub fn first_matches(&self, value: &str) -> Vec<Option<MatchResult>> {
+        pike_vm::search(&self.nfa, value.as_bytes(), false)
+            .into_iter()
+            .map(|found| {
+                found
+                    .map(|(start, end)| MatchResult::new(start, end, value[start..end].to_string()))
+            })
+            .collect()
+    }
+}
+
+/// The set of expressions of a `RegexSet` that matched a value, stored as a bitset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetMatches {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl SetMatches {
+    fn new(len: usize) -> SetMatches {
+        SetMatches {
+            bits: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.bits[index / 64] |= 1 << (index % 64);
+    }
+
+    /// Returns `true` if the expression with the given index matched.
+    pub fn matched(&self, index: usize) -> bool {
+        index < self.len && self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Returns `true` if any expression matched.
+    pub fn matched_any(&self) -> bool {
+        self.bits.iter().any(|&word| word != 0)
+    }
+
+    /// Returns the number of expressions in the set, matched or not.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set had no expressions.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the indexes of the expressions that matched, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&index| self.matched(index))
+    }
+}
@@ -1,4 +1,6 @@
 use crate::regex_class::RegexClass;
+use crate::regex_part::RegexPart;
+use crate::utf8;
 
 /// Represents a single value in a regular expression.
 
@@ -8,48 +10,99 @@ pub enum RegexVal {
     Literal(char),
     /// A wildcard value that matches any single character.
     Wildcard,
+    /// A wildcard value that matches any single character, or a single byte that isn't part of a valid character.
+    AnyByte,
+    /// A raw byte, used by the byte regexes for `\xNN` escapes that aren't ASCII characters.
+    Byte(u8),
     /// A bracket expression that matches any single character in the provided list.
     /// If `is_negated` is `true`, the expression matches any character not in the list.
     BracketExpression { chars: Vec<char>, is_negated: bool },
     /// A character class with an enum RegexClass as value.
     Class(RegexClass),
+    /// A group of alternatives matched as a single value, so a repetition applies to the whole group.
+    /// The capturing groups are numbered by the position of their opening parenthesis, starting at 1,
+    /// and non-capturing groups have no index. Named groups are also capturing.
+    Group {
+        parts: Vec<RegexPart>,
+        index: Option<usize>,
+        name: Option<String>,
+    },
+    /// An anchor matching only at the start of the text, without consuming any character.
+    StartOfText,
+    /// An anchor matching only at the end of the text, without consuming any character.
+    EndOfText,
+    /// An anchor matching at the start of the text or after a newline, used by `^` in multiline mode.
+    StartOfLine,
+    /// An anchor matching at the end of the text or before a newline, used by `$` in multiline mode.
+    EndOfLine,
 }
 
 impl RegexVal {
-    /// Determines if the value matches the beginning of a string.
+    /// Determines if the value matches the beginning of the bytes.
+    /// Apart from `AnyByte` and `Byte`, the values only match valid UTF-8 characters.
     ///
     /// # Arguments
     ///
-    /// * `value` - A string slice to check for a match.
+    /// * `value` - The bytes to check for a match.
     ///
     /// # Returns
     ///
-    /// The length of the matched part of the string, or 0 if there is no match.
-    pub fn matches(&self, value: &str) -> usize {
+    /// The length in bytes of the matched part, or 0 if there is no match.
+    /// Groups and anchors don't match a single character, so they are never matched here but compiled into the automaton.
+    pub fn matches(&self, value: &[u8]) -> usize {
         match self {
-            RegexVal::Literal(l) => {
-                if value.starts_with(*l) {
-                    l.len_utf8()
-                } else {
-                    0
-                }
-            }
-            RegexVal::Wildcard => {
-                if let Some(c) = value.chars().next() {
-                    c.len_utf8()
-                } else {
-                    0
-                }
-            }
-            RegexVal::BracketExpression { chars, is_negated } => match value.chars().next() {
-                Some(c) if chars.contains(&c) != *is_negated => c.len_utf8(),
+            RegexVal::Byte(b) => usize::from(value.first() == Some(b)),
+            RegexVal::AnyByte => match value {
+                [] => 0,
+                _ => utf8::char_len(value),
+            },
+            RegexVal::Group { .. }
+            | RegexVal::StartOfText
+            | RegexVal::EndOfText
+            | RegexVal::StartOfLine
+            | RegexVal::EndOfLine => 0,
+            _ => match utf8::decode_char(value) {
+                Some((c, len)) if self.matches_char(c) => len,
                 _ => 0,
             },
-            RegexVal::Class(class) => value
-                .chars()
-                .next()
-                .filter(|&c| class.matches(&c))
-                .map_or(0, |c| c.len_utf8()),
+        }
+    }
+
+    /// Determines if the value matches the character.
+    fn matches_char(&self, c: char) -> bool {
+        match self {
+            RegexVal::Literal(l) => *l == c,
+            RegexVal::Wildcard | RegexVal::AnyByte => true,
+            RegexVal::BracketExpression { chars, is_negated } => chars.contains(&c) != *is_negated,
+            RegexVal::Class(class) => class.matches(&c),
+            _ => false,
+        }
+    }
+
+    /// Returns how many groups are nested in the value, counting the value itself if it is a group.
+    pub fn nesting_depth(&self) -> usize {
+        match self {
+            RegexVal::Group { parts, .. } => {
+                let inner = parts
+                    .iter()
+                    .flat_map(|part| &part.states)
+                    .map(|state| state.value.nesting_depth())
+                    .max();
+                1 + inner.unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if the value can match without consuming any character.
+    pub fn can_match_empty(&self) -> bool {
+        match self {
+            RegexVal::Group { parts, .. } => parts.iter().any(RegexPart::can_match_empty),
+            RegexVal::StartOfText
+            | RegexVal::EndOfText
+            | RegexVal::StartOfLine
+            | RegexVal::EndOfLine => true,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,74 @@
+use crate::match_result::MatchResult;
+
+/// A way of computing the text that replaces a match, used by `Regex::replace` and `Regex::replace_all`.
+/// It is implemented by templates, given as strings, and by closures that receive the match.
+pub trait Replacer {
+    /// Appends the replacement of the match to `dst`.
+    fn replace_append(&mut self, result: &MatchResult, dst: &mut String);
+}
+
+impl Replacer for &str {
+    fn replace_append(&mut self, result: &MatchResult, dst: &mut String) {
+        expand(self, result, dst);
+    }
+}
+
+impl Replacer for String {
+    fn replace_append(&mut self, result: &MatchResult, dst: &mut String) {
+        expand(self, result, dst);
+    }
+}
+
+impl<F> Replacer for F
+where
+    F: FnMut(&MatchResult) -> String,
+{
+    fn replace_append(&mut self, result: &MatchResult, dst: &mut String) {
+        dst.push_str(&self(result));
+    }
+}
+
+/// Expands a replacement template with the groups of the match, appending the result to `dst`.
+///
+/// The template can refer to the groups as `$1` or `${1}`, and to the named groups as `$name` or `${name}`.
+/// `$0` is the whole match and `$$` is a literal dollar sign. A reference is taken as long as possible,
+/// so `${1}a` is needed to put a letter right after a group. Groups that didn't take part in the match
+/// are replaced by nothing, and a `$` that doesn't start a reference is kept as it is.
+pub fn expand(template: &str, result: &MatchResult, dst: &mut String) {
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        dst.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            dst.push('$');
+            rest = after;
+            continue;
+        }
+        let reference = if let Some(braced) = rest.strip_prefix('{') {
+            braced.find('}').map(|close| (&braced[..close], close + 2))
+        } else {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            Some((&rest[..len], len))
+        };
+        match reference {
+            Some((name, len)) if !name.is_empty() => {
+                dst.push_str(group_text(result, name));
+                rest = &rest[len..];
+            }
+            _ => dst.push('$'),
+        }
+    }
+    dst.push_str(rest);
+}
+
+/// Returns the text captured by the group with the given number or name, or an empty string if there's none.
+fn group_text<'a>(result: &'a MatchResult, reference: &str) -> &'a str {
+    let capture = match reference.parse::<usize>() {
+        Ok(0) => return &result.matched,
+        Ok(index) => result.group(index),
+        Err(_) => result.name(reference),
+    };
+    capture.map_or("", |capture| &capture.matched)
+}
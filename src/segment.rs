@@ -0,0 +1,17 @@
+/// The kind of a segment of a value tokenized by a regex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentKind {
+    /// A match of the regex.
+    Matched,
+    /// The text between two matches, or before the first or after the last one.
+    Unmatched,
+}
+
+/// A segment of a value tokenized by `Regex::tokenize`, with its range in the value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment<'a> {
+    pub kind: SegmentKind,
+    pub start: usize,
+    pub end: usize,
+    pub text: &'a str,
+}
@@ -0,0 +1,247 @@
+use crate::nfa::Nfa;
+use crate::pike_vm::{self, StreamSearch, StreamState};
+use crate::utf8;
+use std::io::{self, Read};
+
+/// The number of bytes read from the stream at a time.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of bytes a stream search can keep in memory, see `StreamFindIter::buffer_limit`.
+pub const DEFAULT_BUFFER_LIMIT: usize = 64 * 1024 * 1024;
+
+/// A match found in a stream, with its absolute byte offsets from the start of the stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamMatch {
+    pub start: usize,
+    pub end: usize,
+    pub matched: Vec<u8>,
+}
+
+impl StreamMatch {
+    pub fn range(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+}
+
+/// An iterator over the successive non-overlapping matches of a regex in a stream, from left to right.
+///
+/// The stream is read in chunks and the automaton is run over each byte once, keeping its threads from one chunk
+/// to the next, so the matches don't need to fit in a line and can cross the boundary between two chunks.
+/// Only the bytes from the start of the earliest thread still alive are kept between reads, so unless a match
+/// is in progress, the memory used is bounded by the chunk size. A match in progress can keep up to the buffer limit,
+/// past which the iterator returns an error, see `buffer_limit`.
+pub struct StreamFindIter<'r, R> {
+    nfa: &'r Nfa,
+    reader: R,
+    chunk_size: usize,
+    /// The number of bytes the buffer can keep before reading more of the stream.
+    buffer_limit: usize,
+    buffer: Vec<u8>,
+    /// The offset in the stream of the first byte of the buffer.
+    offset: usize,
+    /// The position of the buffer where the next match is searched from.
+    at: usize,
+    eof: bool,
+    done: bool,
+    /// The search in progress, resumed after every read.
+    state: StreamState,
+    /// Whether the lines of the matches are kept whole in the buffer, see `whole_lines`.
+    whole_lines: bool,
+    /// The position of the buffer up to which the newlines were counted, and the number of its line,
+    /// so `line_number` only counts the newlines since the last position asked for.
+    counted: usize,
+    line_number: usize,
+}
+
+impl<'r, R: Read> StreamFindIter<'r, R> {
+    /// Creates an iterator over the matches of the automaton in the stream, reading `chunk_size` bytes at a time.
+    pub fn new(nfa: &'r Nfa, reader: R, chunk_size: usize) -> Self {
+        StreamFindIter {
+            nfa,
+            reader,
+            chunk_size: chunk_size.max(1),
+            buffer_limit: DEFAULT_BUFFER_LIMIT,
+            buffer: vec![],
+            offset: 0,
+            at: 0,
+            eof: false,
+            done: false,
+            state: StreamState::default(),
+            whole_lines: false,
+            counted: 0,
+            line_number: 1,
+        }
+    }
+
+    /// Keeps the buffer from the start of the line where a match can still start, instead of from that position,
+    /// so the lines touched by every match can be returned by `matched_lines`.
+    /// The memory used is then bounded by the length of the lines, instead of by the chunk size.
+    pub fn whole_lines(mut self) -> Self {
+        self.whole_lines = true;
+        self
+    }
+
+    /// Sets the number of bytes kept in memory past which the iterator returns an `OutOfMemory` error and stops,
+    /// instead of reading more of the stream: the bytes of a match in progress, and with `whole_lines`, of its lines.
+    pub fn buffer_limit(mut self, limit: usize) -> Self {
+        self.buffer_limit = limit;
+        self
+    }
+
+    /// Returns the offset in the stream and the content of the lines touched by the match last returned,
+    /// without the newline at their end, reading the stream up to the end of the last line if needed.
+    /// It must be called before advancing the iterator, on an iterator created with `whole_lines`.
+    pub fn matched_lines(&mut self, found: &StreamMatch) -> io::Result<(usize, &[u8])> {
+        let start = found.start - self.offset;
+        let end = found.end - self.offset;
+        let lines_start = self.buffer[..start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let lines_end = if end > start && self.buffer[end - 1] == b'\n' {
+            end - 1
+        } else {
+            let mut searched = end;
+            loop {
+                if let Some(newline) = self.buffer[searched..].iter().position(|&b| b == b'\n') {
+                    break searched + newline;
+                }
+                searched = self.buffer.len();
+                if self.eof {
+                    break searched;
+                }
+                self.fill()?;
+            }
+        };
+        Ok((
+            self.offset + lines_start,
+            &self.buffer[lines_start..lines_end],
+        ))
+    }
+
+    /// Returns the 1-based number of the line of the byte at the given offset in the stream,
+    /// which must still be in the buffer, like the start of a match or of its lines before advancing the iterator.
+    /// The newlines are counted once as the offsets asked for move forward, and as the buffer is discarded.
+    pub fn line_number(&mut self, offset: usize) -> usize {
+        let position = offset - self.offset;
+        if position >= self.counted {
+            self.line_number += count_newlines(&self.buffer[self.counted..position]);
+        } else {
+            self.line_number -= count_newlines(&self.buffer[position..self.counted]);
+        }
+        self.counted = position;
+        self.line_number
+    }
+
+    /// Appends the next chunk of the stream to the buffer, or marks the end of the stream if there is nothing left.
+    /// Fails if the buffer already holds more than the buffer limit.
+    fn fill(&mut self) -> io::Result<()> {
+        let len = self.buffer.len();
+        if len > self.buffer_limit {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                "the match in progress is longer than the buffer limit",
+            ));
+        }
+        self.buffer.resize(len + self.chunk_size, 0);
+        loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(read) => {
+                    self.buffer.truncate(len + read);
+                    self.eof = read == 0;
+                    return Ok(());
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.buffer.truncate(len);
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    /// Discards the bytes of the buffer before `keep`, except the one right before it,
+    /// which the line anchors look at, and moves the search in progress, or the next one, back with the buffer.
+    /// With `whole_lines`, the line of `keep` is kept too, from the newline before it.
+    fn discard(&mut self, keep: usize) {
+        let discarded = if self.whole_lines {
+            self.buffer[..keep]
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .unwrap_or(0)
+        } else {
+            keep.saturating_sub(1)
+        };
+        if self.counted < discarded {
+            self.line_number += count_newlines(&self.buffer[self.counted..discarded]);
+            self.counted = discarded;
+        }
+        self.counted -= discarded;
+        self.buffer.drain(..discarded);
+        self.offset += discarded;
+        self.at = keep - discarded;
+        self.state.shift(discarded);
+    }
+
+    /// Moves the search one character past the empty match at `end`,
+    /// reading enough of the stream to know the length of that character.
+    fn skip_empty_match(&mut self, end: usize) -> io::Result<()> {
+        while self.buffer.len() - end < 4 && !self.eof {
+            self.fill()?;
+        }
+        if end == self.buffer.len() {
+            self.done = true;
+        } else {
+            self.at = end + utf8::char_len(&self.buffer[end..]);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamFindIter<'_, R> {
+    type Item = io::Result<StreamMatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let is_start = self.offset == 0;
+            let search = pike_vm::search_stream(
+                self.nfa,
+                &self.buffer,
+                self.at,
+                &mut self.state,
+                is_start,
+                self.eof,
+            );
+            match search {
+                StreamSearch::Match(start, end) => {
+                    let found = StreamMatch {
+                        start: self.offset + start,
+                        end: self.offset + end,
+                        matched: self.buffer[start..end].to_vec(),
+                    };
+                    let moved = if end > start {
+                        self.at = end;
+                        Ok(())
+                    } else {
+                        self.skip_empty_match(end)
+                    };
+                    self.done |= moved.is_err();
+                    return Some(moved.map(|_| found));
+                }
+                StreamSearch::NoMatch => self.done = true,
+                StreamSearch::NeedMore(keep) => {
+                    self.discard(keep);
+                    if let Err(error) = self.fill() {
+                        self.done = true;
+                        return Some(Err(error));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn count_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
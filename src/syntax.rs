@@ -0,0 +1,345 @@
+use crate::error::RegexError;
+use crate::regex::ParseOptions;
+use crate::regex_class::RegexClass;
+use crate::regex_value::RegexVal;
+
+/// The dialect used to write a regular expression.
+/// Every dialect is translated into the same tokens, so they share the parser and the engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// POSIX basic regular expressions, as used by `grep`.
+    /// `\(`, `\)`, `\{` and `\}` are special, while `+`, `?` and `|` are literals unless escaped.
+    Basic,
+    /// POSIX extended regular expressions, as used by `egrep`.
+    #[default]
+    Extended,
+    /// Perl compatible regular expressions, as used by `grep -P`.
+    /// It extends the extended syntax with non-capturing and named groups, `\xNN`, `\u{NNNN}`, `\t`, `\n`
+    /// and `\r` escapes, `\Q...\E` quoting, the `\d`, `\w` and `\s` classes and the `\A` and `\z` anchors.
+    Perl,
+}
+
+/// The kind of a group, decided by the syntax used to open it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupKind {
+    /// A group that captures the text it matches, numbered by its position.
+    Capturing,
+    /// A group that only groups its content, like `(?:...)`.
+    NonCapturing,
+    /// A capturing group that can also be referred to by its name, like `(?<name>...)`.
+    Named(String),
+}
+
+/// A meaningful unit of a regular expression, independent of the dialect it was written in.
+#[derive(Debug, Clone)]
+pub enum Token {
+    /// A character that only matches itself.
+    Literal(char),
+    /// The `.` wildcard.
+    Dot,
+    /// The `*` repetition.
+    Star,
+    /// The `+` repetition.
+    Plus,
+    /// The `?` repetition.
+    Question,
+    /// The content of a `{m,n}` repetition, without the braces.
+    Interval(String),
+    /// The content of a bracket expression, without the brackets.
+    Bracket(Vec<char>),
+    /// A `\xNN` escape, which is a character in string expressions and a raw byte in byte expressions.
+    Byte(u8),
+    /// A value fully decided by the tokenizer, like the `\d` class or the `\A` anchor.
+    Value(RegexVal),
+    /// The start of a group.
+    GroupOpen(GroupKind),
+    /// The end of a group.
+    GroupClose,
+    /// The `|` between two alternatives.
+    Alternation,
+    /// The `^` anchor.
+    Caret,
+    /// The `$` anchor.
+    Dollar,
+}
+
+/// Splits an expression written in the given dialect into tokens.
+/// The expressions matched against bytes accept the `\xNN` escapes in every dialect, not only in the Perl one,
+/// and so do the multiline expressions with the `\n` escape, which lets them match across lines.
+pub fn tokenize(
+    expression: &str,
+    syntax: Syntax,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, RegexError> {
+    match syntax {
+        Syntax::Basic => tokenize_basic(expression, options),
+        Syntax::Extended => tokenize_extended(expression, options),
+        Syntax::Perl => tokenize_perl(expression),
+    }
+}
+
+/// Splits an extended regular expression into tokens.
+fn tokenize_extended(expression: &str, options: &ParseOptions) -> Result<Vec<Token>, RegexError> {
+    let mut tokens = vec![];
+    let mut chars_iter = expression.chars();
+    while let Some(c) = chars_iter.next() {
+        match c {
+            '\\' if options.bytes && chars_iter.as_str().starts_with('x') => {
+                chars_iter.next();
+                tokens.push(get_hex_byte(&mut chars_iter)?);
+            }
+            '\\' if options.multiline && chars_iter.as_str().starts_with('n') => {
+                chars_iter.next();
+                tokens.push(Token::Literal('\n'));
+            }
+            _ => tokens.push(get_extended_token(c, &mut chars_iter)?),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the token of an extended expression that starts with the character `c`,
+/// consuming the rest of the token from the iterator.
+fn get_extended_token(c: char, chars_iter: &mut std::str::Chars<'_>) -> Result<Token, RegexError> {
+    let token = match c {
+        '.' => Token::Dot,
+        '*' => Token::Star,
+        '+' => Token::Plus,
+        '?' => Token::Question,
+        '|' => Token::Alternation,
+        '(' => Token::GroupOpen(GroupKind::Capturing),
+        ')' => Token::GroupClose,
+        '^' => Token::Caret,
+        '$' => Token::Dollar,
+        '[' => get_bracket_token(chars_iter)?,
+        '{' => Token::Interval(get_interval(chars_iter, "}")?),
+        '\\' => match chars_iter.next() {
+            Some(literal) => Token::Literal(literal),
+            None => return Err(RegexError::InvalidRegularExpression),
+        },
+        _ => Token::Literal(c),
+    };
+    Ok(token)
+}
+
+/// Splits a Perl compatible regular expression into tokens.
+/// It is tokenized like an extended expression, except for the groups and escapes specific to Perl.
+fn tokenize_perl(expression: &str) -> Result<Vec<Token>, RegexError> {
+    let mut tokens = vec![];
+    let mut chars_iter = expression.chars();
+    while let Some(c) = chars_iter.next() {
+        match c {
+            '(' if chars_iter.as_str().starts_with('?') => {
+                chars_iter.next();
+                tokens.push(Token::GroupOpen(get_group_kind(&mut chars_iter)?));
+            }
+            '\\' => match chars_iter.next() {
+                Some('Q') => {
+                    let rest = chars_iter.as_str();
+                    let (quoted, after) = match rest.find("\\E") {
+                        Some(end) => (&rest[..end], &rest[end + 2..]),
+                        None => (rest, ""),
+                    };
+                    tokens.extend(quoted.chars().map(Token::Literal));
+                    chars_iter = after.chars();
+                }
+                Some('E') => (),
+                Some(escape) => tokens.push(get_perl_escape(escape, &mut chars_iter)?),
+                None => return Err(RegexError::InvalidRegularExpression),
+            },
+            _ => tokens.push(get_extended_token(c, &mut chars_iter)?),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the kind of a Perl group, after its `(?`.
+/// Only the non-capturing `(?:...)` and the named `(?<name>...)` or `(?P<name>...)` groups are supported.
+fn get_group_kind(chars_iter: &mut std::str::Chars<'_>) -> Result<GroupKind, RegexError> {
+    match chars_iter.next() {
+        Some(':') => Ok(GroupKind::NonCapturing),
+        Some('P') if chars_iter.next() == Some('<') => get_group_name(chars_iter),
+        Some('<') => get_group_name(chars_iter),
+        _ => Err(RegexError::InvalidRegularExpression),
+    }
+}
+
+/// Collects the name of a named group until its closing `>`.
+/// A name starts with a letter or an underscore, followed by letters, digits or underscores.
+fn get_group_name(chars_iter: &mut std::str::Chars<'_>) -> Result<GroupKind, RegexError> {
+    let name = get_interval(chars_iter, ">")?;
+    let mut name_chars = name.chars();
+    let valid_start = name_chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if valid_start && name_chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(GroupKind::Named(name))
+    } else {
+        Err(RegexError::InvalidGroupName)
+    }
+}
+
+/// Returns the token of a Perl escape sequence, after its backslash.
+/// Any other escaped letter or digit is rejected, and the remaining characters are taken literally.
+fn get_perl_escape(
+    escape: char,
+    chars_iter: &mut std::str::Chars<'_>,
+) -> Result<Token, RegexError> {
+    let token = match escape {
+        't' => Token::Literal('\t'),
+        'n' => Token::Literal('\n'),
+        'r' => Token::Literal('\r'),
+        'f' => Token::Literal('\x0c'),
+        'v' => Token::Literal('\x0b'),
+        'x' if chars_iter.as_str().starts_with('{') => {
+            chars_iter.next();
+            Token::Literal(parse_code_point(&get_interval(chars_iter, "}")?)?)
+        }
+        'x' => get_hex_byte(chars_iter)?,
+        'u' if chars_iter.next() == Some('{') => {
+            Token::Literal(parse_code_point(&get_interval(chars_iter, "}")?)?)
+        }
+        'd' => Token::Value(RegexVal::Class(RegexClass::Digit)),
+        's' => Token::Value(RegexVal::Class(RegexClass::Space)),
+        'w' => Token::Value(word_value(false)),
+        'D' => Token::Value(negated_value(('0'..='9').collect())),
+        'S' => Token::Value(negated_value(" \t\n\r\x0b\x0c".chars().collect())),
+        'W' => Token::Value(word_value(true)),
+        'A' => Token::Value(RegexVal::StartOfText),
+        'z' => Token::Value(RegexVal::EndOfText),
+        c if c.is_ascii_alphanumeric() => return Err(RegexError::InvalidRegularExpression),
+        c => Token::Literal(c),
+    };
+    Ok(token)
+}
+
+/// Returns the token of a `\xNN` escape, after its `\x`, consuming its two hexadecimal digits.
+fn get_hex_byte(chars_iter: &mut std::str::Chars<'_>) -> Result<Token, RegexError> {
+    let rest = chars_iter.as_str();
+    let digits = rest
+        .get(..2)
+        .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or(RegexError::InvalidRegularExpression)?;
+    *chars_iter = rest[2..].chars();
+    u8::from_str_radix(digits, 16)
+        .map(Token::Byte)
+        .map_err(|_| RegexError::InvalidRegularExpression)
+}
+
+/// Converts hexadecimal digits into the character with that code point.
+fn parse_code_point(digits: &str) -> Result<char, RegexError> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(RegexError::InvalidRegularExpression);
+    }
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or(RegexError::InvalidRegularExpression)
+}
+
+/// Returns the value of the `\w` class, letters, digits and the underscore, or of its negation `\W`.
+fn word_value(is_negated: bool) -> RegexVal {
+    let chars = ('a'..='z').chain('A'..='Z').chain('0'..='9').chain(['_']);
+    RegexVal::BracketExpression {
+        chars: chars.collect(),
+        is_negated,
+    }
+}
+
+/// Returns a value matching any character except the given ones.
+fn negated_value(chars: Vec<char>) -> RegexVal {
+    RegexVal::BracketExpression {
+        chars,
+        is_negated: true,
+    }
+}
+
+/// Splits a basic regular expression into tokens.
+/// The `*` is a literal at the start of the expression, of a group or of an alternative,
+/// and the anchors are only special at the start and end of them.
+fn tokenize_basic(expression: &str, options: &ParseOptions) -> Result<Vec<Token>, RegexError> {
+    let mut tokens = vec![];
+    let mut chars_iter = expression.chars();
+    while let Some(c) = chars_iter.next() {
+        let at_start = matches!(
+            tokens.last(),
+            None | Some(Token::GroupOpen(_)) | Some(Token::Alternation)
+        );
+        let token = match c {
+            '.' => Token::Dot,
+            '*' if at_start || matches!(tokens.last(), Some(Token::Caret)) => Token::Literal('*'),
+            '*' => Token::Star,
+            '^' if at_start => Token::Caret,
+            '$' if is_basic_end(chars_iter.as_str()) => Token::Dollar,
+            '[' => get_bracket_token(&mut chars_iter)?,
+            '\\' => match chars_iter.next() {
+                Some('(') => Token::GroupOpen(GroupKind::Capturing),
+                Some(')') => Token::GroupClose,
+                Some('|') => Token::Alternation,
+                Some('+') => Token::Plus,
+                Some('?') => Token::Question,
+                Some('{') => Token::Interval(get_interval(&mut chars_iter, "\\}")?),
+                Some('x') if options.bytes => get_hex_byte(&mut chars_iter)?,
+                Some('n') if options.multiline => Token::Literal('\n'),
+                Some(literal) => Token::Literal(literal),
+                None => return Err(RegexError::InvalidRegularExpression),
+            },
+            _ => Token::Literal(c),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Checks if a `$` followed by the given rest of a basic expression is at the end of the expression,
+/// of a group or of an alternative.
+fn is_basic_end(rest: &str) -> bool {
+    rest.is_empty() || rest.starts_with("\\)") || rest.starts_with("\\|")
+}
+
+/// Collects the content of a repetition interval until the given closing delimiter.
+fn get_interval(chars_iter: &mut std::str::Chars<'_>, close: &str) -> Result<String, RegexError> {
+    let rest = chars_iter.as_str();
+    match rest.find(close) {
+        Some(end) => {
+            let content = rest[..end].to_string();
+            *chars_iter = rest[end + close.len()..].chars();
+            Ok(content)
+        }
+        None => Err(RegexError::InvalidRegularExpression),
+    }
+}
+
+/// Collects the elements inside an expression that starts with a bracket.
+/// A character class like `[[:alpha:]]` is collected as `[:alpha:`, consuming both closing brackets.
+fn get_bracket_token(chars_iter: &mut std::str::Chars<'_>) -> Result<Token, RegexError> {
+    let expression = get_expression_inside_bracket(chars_iter)?;
+    if is_character_class(&expression) && chars_iter.next() != Some(']') {
+        return Err(RegexError::UnmatchedBracket);
+    }
+    Ok(Token::Bracket(expression))
+}
+
+/// Checks if the elements inside a bracket have the format of a character class, like `[:alpha:`.
+pub fn is_character_class(expression: &[char]) -> bool {
+    expression.first() == Some(&'[')
+        && RegexClass::is_character_class(&expression[1..].iter().collect::<String>())
+}
+
+/// Collect the elements inside an expression that starts with a bracket and return them in a vector.
+/// Fails if the bracket is never closed or is empty.
+fn get_expression_inside_bracket(
+    chars_iter: &mut std::str::Chars<'_>,
+) -> Result<Vec<char>, RegexError> {
+    let mut expression = vec![];
+    for c in chars_iter.by_ref() {
+        if c == ']' {
+            if expression.is_empty() {
+                break;
+            }
+            return Ok(expression);
+        }
+        expression.push(c);
+    }
+    Err(RegexError::UnmatchedBracket)
+}
@@ -0,0 +1,19 @@
+/// Decodes the character at the start of the bytes.
+/// Returns the character and its length in bytes, or `None` if the bytes don't start with a valid UTF-8 character.
+pub fn decode_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match *bytes.first()? {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return None,
+    };
+    let encoded = std::str::from_utf8(bytes.get(..len)?).ok()?;
+    encoded.chars().next().map(|c| (c, len))
+}
+
+/// Returns the length of the character at the start of the bytes, or 1 if they don't start with a valid one,
+/// so invalid bytes are stepped over one at a time.
+pub fn char_len(bytes: &[u8]) -> usize {
+    decode_char(bytes).map_or(1, |(_, len)| len)
+}
@@ -0,0 +1,238 @@
+use crate::glob::Glob;
+use crate::ignore::{self, IgnoreFile};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// An error found while walking a directory: the path it happened at and its cause.
+#[derive(Debug)]
+pub struct WalkError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+/// Walks directories to find the files to search.
+#[derive(Debug, Clone)]
+pub struct Walker {
+    follow_links: bool,
+    max_depth: Option<usize>,
+    /// Whether the paths matched by the ignore files of the directories are skipped, see `IgnoreFile`.
+    ignore_files: bool,
+    /// Whether the hidden files and directories, whose name starts with `.`, are walked.
+    hidden: bool,
+    /// The files found must match one of these globs, if there is any.
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    exclude_dir: Vec<Glob>,
+}
+
+impl Default for Walker {
+    fn default() -> Self {
+        Walker::new()
+    }
+}
+
+impl Walker {
+    /// Creates a walker that doesn't follow the symbolic links found in the directories, has no depth limit,
+    /// and skips the hidden files and the ones ignored by the ignore files.
+    pub fn new() -> Walker {
+        Walker {
+            follow_links: false,
+            max_depth: None,
+            ignore_files: true,
+            hidden: false,
+            include: vec![],
+            exclude: vec![],
+            exclude_dir: vec![],
+        }
+    }
+
+    /// Sets whether the symbolic links found in the directories are followed, or skipped.
+    /// The root given to `walk` is always followed.
+    pub fn follow_links(&mut self, yes: bool) -> &mut Walker {
+        self.follow_links = yes;
+        self
+    }
+
+    /// Sets how many directories deep the walk goes, `Some(0)` only yields the root, `None` removes the limit.
+    pub fn max_depth(&mut self, depth: Option<usize>) -> &mut Walker {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets whether the `.gitignore`, `.ignore` and `.git/info/exclude` files found in the directories are
+    /// respected, see `ignore::IGNORE_FILES`. They only apply to the paths under their directory, and the ones
+    /// in deeper directories take precedence.
+    pub fn ignore_files(&mut self, yes: bool) -> &mut Walker {
+        self.ignore_files = yes;
+        self
+    }
+
+    /// Sets whether the hidden files and directories, whose name starts with `.`, are walked.
+    pub fn hidden(&mut self, yes: bool) -> &mut Walker {
+        self.hidden = yes;
+        self
+    }
+
+    /// Only yields the files that match any of the globs, or every file if there are none.
+    /// The globs with a `/` are matched against the path relative to the root, see `Glob::is_match`.
+    pub fn include(&mut self, globs: Vec<Glob>) -> &mut Walker {
+        self.include = globs;
+        self
+    }
+
+    /// Skips the files that match any of the globs.
+    pub fn exclude(&mut self, globs: Vec<Glob>) -> &mut Walker {
+        self.exclude = globs;
+        self
+    }
+
+    /// Skips the directories that match any of the globs, and everything under them.
+    pub fn exclude_dir(&mut self, globs: Vec<Glob>) -> &mut Walker {
+        self.exclude_dir = globs;
+        self
+    }
+
+    /// Returns `true` if the file found at the path relative to the root is searched.
+    fn selects_file(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.is_match(path)))
+            && !self.exclude.iter().any(|glob| glob.is_match(path))
+    }
+
+    /// Returns `true` if the directory found at the path relative to the root is walked.
+    fn selects_dir(&self, path: &Path) -> bool {
+        !self.exclude_dir.iter().any(|glob| glob.is_match(path))
+    }
+
+    /// Returns an iterator over the regular files under the root, or the root itself if it isn't a directory.
+    /// The entries of every directory are visited sorted by name, so the files always come in the same order.
+    /// The root itself is never filtered out by the globs.
+    pub fn walk<P: AsRef<Path>>(&self, root: P) -> Walk {
+        Walk {
+            walker: self.clone(),
+            root: root.as_ref().to_path_buf(),
+            pending: vec![Pending {
+                path: root.as_ref().to_path_buf(),
+                depth: 0,
+                ancestors: vec![],
+                ignores: vec![],
+            }],
+        }
+    }
+}
+
+/// A path waiting to be visited, with the canonical paths of the directories that contain it,
+/// and the ignore files that apply to it.
+struct Pending {
+    path: PathBuf,
+    depth: usize,
+    ancestors: Vec<PathBuf>,
+    ignores: Vec<Arc<IgnoreFile>>,
+}
+
+/// The iterator over the files of a walk, see `Walker::walk`.
+pub struct Walk {
+    walker: Walker,
+    root: PathBuf,
+    /// The paths left to visit, the next one last.
+    pending: Vec<Pending>,
+}
+
+impl Walk {
+    /// Visits the path, returning it if it is a file to search, or queuing its entries if it is a directory.
+    fn visit(&mut self, pending: Pending) -> Result<Option<PathBuf>, io::Error> {
+        let metadata = if pending.depth == 0 || self.walker.follow_links {
+            fs::metadata(&pending.path)?
+        } else {
+            fs::symlink_metadata(&pending.path)?
+        };
+        if pending.depth == 0 && !metadata.is_dir() {
+            return Ok(Some(pending.path));
+        }
+        if pending.depth > 0 && self.skips(&pending.path, metadata.is_dir(), &pending.ignores) {
+            return Ok(None);
+        }
+        let relative = pending
+            .path
+            .strip_prefix(&self.root)
+            .unwrap_or(&pending.path);
+        if metadata.is_file() {
+            let selected = self.walker.selects_file(relative);
+            return Ok(selected.then_some(pending.path));
+        }
+        if !metadata.is_dir() || (pending.depth > 0 && !self.walker.selects_dir(relative)) {
+            return Ok(None);
+        }
+        if self
+            .walker
+            .max_depth
+            .is_some_and(|max_depth| pending.depth >= max_depth)
+        {
+            return Ok(None);
+        }
+
+        // Only the followed links can lead back to a directory being walked.
+        let mut ancestors = pending.ancestors;
+        if self.walker.follow_links {
+            let canonical = fs::canonicalize(&pending.path)?;
+            if ancestors.contains(&canonical) {
+                return Err(io::Error::other("recursive directory loop"));
+            }
+            ancestors.push(canonical);
+        }
+        let mut ignores = pending.ignores;
+        if self.walker.ignore_files {
+            ignores.extend(
+                IgnoreFile::from_dir(&pending.path)
+                    .into_iter()
+                    .map(Arc::new),
+            );
+        }
+        let mut entries = fs::read_dir(&pending.path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        entries.sort();
+        for path in entries.into_iter().rev() {
+            self.pending.push(Pending {
+                path,
+                depth: pending.depth + 1,
+                ancestors: ancestors.clone(),
+                ignores: ignores.clone(),
+            });
+        }
+        Ok(None)
+    }
+
+    /// Returns `true` if the path found in a directory is hidden or ignored, and the walker skips it.
+    fn skips(&self, path: &Path, is_dir: bool, ignores: &[Arc<IgnoreFile>]) -> bool {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        (hidden && !self.walker.hidden)
+            || (self.walker.ignore_files && ignore::is_ignored(ignores, path, is_dir))
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<PathBuf, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pending) = self.pending.pop() {
+            let path = pending.path.clone();
+            match self.visit(pending) {
+                Ok(Some(file)) => return Some(Ok(file)),
+                Ok(None) => continue,
+                Err(error) => return Some(Err(WalkError { path, error })),
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,86 @@
+use grep_rustico::aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use grep_rustico::matcher::Matcher;
+use grep_rustico::regex::Regex;
+
+fn ranges(automaton: &AhoCorasick, value: &str) -> Vec<(usize, usize)> {
+    automaton.find_iter(value).map(|m| m.range()).collect()
+}
+
+#[test]
+fn test_leftmost_first() {
+    let automaton = AhoCorasick::new(["Sam", "Samwise"]);
+    assert_eq!(ranges(&automaton, "Samwise"), vec![(0, 3)]);
+
+    let automaton = AhoCorasick::new(["abcd", "b", "bcd"]);
+    assert_eq!(ranges(&automaton, "xabcdx bcd"), vec![(1, 5), (7, 8)]);
+}
+
+#[test]
+fn test_leftmost_longest() {
+    let automaton = AhoCorasickBuilder::new()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(["Sam", "Samwise", "wise guy"]);
+    assert_eq!(ranges(&automaton, "Samwise guy"), vec![(0, 7)]);
+
+    let automaton = AhoCorasickBuilder::new()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(["b", "abc", "abcde"]);
+    assert_eq!(ranges(&automaton, "xabcdex b"), vec![(1, 6), (8, 9)]);
+}
+
+#[test]
+fn test_ascii_case_insensitive() {
+    let automaton = AhoCorasickBuilder::new()
+        .ascii_case_insensitive(true)
+        .build(["error", "WARN"]);
+    let found = automaton.find("an Error and a warning").unwrap();
+    assert_eq!(found.matched, "Error");
+    assert_eq!(
+        ranges(&automaton, "an Error and a warning"),
+        vec![(3, 8), (15, 19)]
+    );
+    assert!(AhoCorasick::new(["error"]).find("ERROR").is_none());
+}
+
+#[test]
+fn test_many_patterns() {
+    let patterns: Vec<String> = (0..20000).map(|i| format!("host-{}.example", i)).collect();
+    let automaton = AhoCorasick::new(&patterns);
+    assert_eq!(automaton.pattern_count(), 20000);
+    let found = automaton.find("blocked: host-12345.example.org").unwrap();
+    assert_eq!(found.matched, "host-12345.example");
+    assert!(!automaton.is_match(b"host-20000.example"));
+}
+
+#[test]
+fn test_matcher_uses_aho_corasick_for_literals() {
+    assert!(matches!(
+        Matcher::new(["cat|dog", "bird"]).unwrap(),
+        Matcher::Literal(_)
+    ));
+    assert!(matches!(
+        Matcher::new(["cat", "d.g"]).unwrap(),
+        Matcher::Regex(_)
+    ));
+    assert!(matches!(Matcher::new(["^cat"]).unwrap(), Matcher::Regex(_)));
+
+    let matcher = Matcher::new(["d.g", "cat"]).unwrap();
+    assert_eq!(matcher.find("a dig").unwrap().unwrap().matched, "dig");
+    assert!(matcher.find("a cow").unwrap().is_none());
+}
+
+#[test]
+fn test_regex_literal() {
+    let regex = Regex::literal("a.b[0]*\n$5");
+    assert_eq!(regex.literals().unwrap(), vec!["a.b[0]*", "$5"]);
+    assert!(regex.match_expression("axb0").unwrap().is_none());
+    let found = regex.match_expression("x a.b[0]* y").unwrap().unwrap();
+    assert_eq!(found.matched, "a.b[0]*");
+    assert!(regex.match_expression("costs $5").unwrap().is_some());
+
+    let matcher = Matcher::fixed_strings(["a.b[0]*"]);
+    assert_eq!(
+        matcher.find("the a.b[0]* index").unwrap().unwrap().range(),
+        (4, 11)
+    );
+}
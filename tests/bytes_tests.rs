@@ -0,0 +1,48 @@
+use grep_rustico::bytes::Regex;
+use grep_rustico::syntax::Syntax;
+
+#[test]
+fn test_invalid_utf8_haystack() {
+    let regex = Regex::new("au l[a-z]+").unwrap();
+    let found = regex.find(b"caf\xe9 au lait").unwrap();
+    assert_eq!(found.range(), (5, 12));
+    assert_eq!(found.matched, b"au lait");
+}
+
+#[test]
+fn test_hex_escapes() {
+    let regex = Regex::new("caf\\xe9").unwrap();
+    assert!(regex.is_match(b"un caf\xe9"));
+    assert!(!regex.is_match("un café".as_bytes()));
+
+    let regex = Regex::with_syntax("\\x41\\(\\xff\\)*", Syntax::Basic).unwrap();
+    assert_eq!(regex.find(b"xA\xff\xff").unwrap().matched, b"A\xff\xff");
+
+    let regex = Regex::with_syntax("\\x41\\xe9", Syntax::Perl).unwrap();
+    assert!(regex.is_match(b"A\xe9"));
+    assert!(Regex::new("\\xg1").is_err());
+}
+
+#[test]
+fn test_dot_matches_any_byte() {
+    let regex = Regex::new("caf.").unwrap();
+    assert!(!regex.is_match(b"caf\xe9"));
+    assert_eq!(regex.find("café".as_bytes()).unwrap().end, 5);
+
+    let regex = Regex::with_options("caf.$", Syntax::Extended, true).unwrap();
+    assert!(regex.is_match(b"caf\xe9"));
+    assert_eq!(regex.find("café".as_bytes()).unwrap().end, 5);
+}
+
+#[test]
+fn test_find_iter() {
+    let regex = Regex::new("[0-9]+").unwrap();
+    let matches: Vec<&[u8]> = regex
+        .find_iter(b"1\xff22\x80333")
+        .map(|found| found.matched)
+        .collect();
+    assert_eq!(matches, vec![&b"1"[..], b"22", b"333"]);
+
+    let regex = Regex::new("x*").unwrap();
+    assert_eq!(regex.find_iter(b"\xff\xfe").count(), 3);
+}
@@ -0,0 +1,73 @@
+use grep_rustico::error::RegexError;
+use grep_rustico::matcher::Matcher;
+use grep_rustico::regex::Regex;
+use grep_rustico::syntax::Syntax;
+
+fn perl(expression: &str) -> Regex {
+    Regex::with_syntax(expression, Syntax::Perl).unwrap()
+}
+
+#[test]
+fn test_named_groups() {
+    let regex = perl("(?<year>\\d{4})-(?<month>\\d{2})");
+    let result = regex.match_expression("date: 2024-05-17").unwrap().unwrap();
+    assert_eq!(result.matched, "2024-05");
+
+    let year = result.name("year").unwrap();
+    assert_eq!((year.start, year.end), (6, 10));
+    assert_eq!(year.matched, "2024");
+    assert_eq!(result.name("month").unwrap().matched, "05");
+    assert!(result.name("day").is_none());
+
+    let named: Vec<_> = result.named_groups().collect();
+    assert_eq!(
+        named,
+        vec![("year", (6, 10), "2024"), ("month", (11, 13), "05")]
+    );
+}
+
+#[test]
+fn test_numbered_groups() {
+    let regex = Regex::new("(a|b)+(x)?c").unwrap();
+    let result = regex.match_expression("zabac").unwrap().unwrap();
+    assert_eq!(result.matched, "abac");
+    assert_eq!(result.group(1).unwrap().matched, "a");
+    assert!(result.group(2).is_none());
+    assert_eq!(result.group(1).unwrap().name, None);
+}
+
+#[test]
+fn test_groups_after_backtracking() {
+    let regex = perl("(?<first>a*)(?<second>ab)");
+    let result = regex.match_expression("aaab").unwrap().unwrap();
+    assert_eq!(result.name("first").unwrap().matched, "aa");
+    assert_eq!(result.name("second").unwrap().matched, "ab");
+}
+
+#[test]
+fn test_capture_names() {
+    let regex = perl("(?<outer>(?<inner>a)(b))|(?:c)(?P<last>d)");
+    assert_eq!(regex.capture_names(), vec!["outer", "inner", "last"]);
+    assert!(Regex::new("(a)(b)").unwrap().capture_names().is_empty());
+
+    let result = regex.match_expression("cd").unwrap().unwrap();
+    assert_eq!(result.name("last").unwrap().matched, "d");
+    assert_eq!(result.name("last").unwrap().index, 4);
+}
+
+#[test]
+fn test_groups_of_several_patterns() {
+    let matcher = Matcher::with_syntax(["(?<word>h)(o)la", "(m)un(do)"], Syntax::Perl).unwrap();
+    let result = matcher.find("un mundo").unwrap().unwrap();
+    assert!(result.group(1).is_none());
+    assert_eq!(result.group(3).unwrap().matched, "m");
+    assert_eq!(result.group(4).unwrap().matched, "do");
+    let result = matcher.find("hola").unwrap().unwrap();
+    assert_eq!(result.name("word").unwrap().index, 1);
+    assert_eq!(result.group(2).unwrap().matched, "o");
+
+    assert_eq!(
+        Matcher::with_syntax(["(?<word>a)", "(?<word>b)"], Syntax::Perl).unwrap_err(),
+        RegexError::InvalidGroupName
+    );
+}
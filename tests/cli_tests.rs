@@ -0,0 +1,271 @@
+use grep_rustico::cli::{parse_args, ArgsError, Command, Config};
+use grep_rustico::file_handler::{ColorChoice, Report};
+use grep_rustico::syntax::Syntax;
+
+fn search(args: &[&str]) -> Config {
+    match parse_args(args.iter().copied()) {
+        Ok(Command::Search(config)) => *config,
+        result => panic!("expected a search, got {:?}", result),
+    }
+}
+
+fn error(args: &[&str]) -> ArgsError {
+    parse_args(args.iter().copied()).unwrap_err()
+}
+
+#[test]
+fn test_pattern_and_files() {
+    let config = search(&["ab+c", "a.txt", "b.txt"]);
+    assert_eq!(config.patterns, vec!["ab+c"]);
+    assert_eq!(config.files, vec!["a.txt", "b.txt"]);
+    assert_eq!(config.syntax, Syntax::Extended);
+    assert_eq!(
+        config,
+        Config {
+            patterns: vec!["ab+c".to_string()],
+            files: vec!["a.txt".to_string(), "b.txt".to_string()],
+            ..Config::default()
+        }
+    );
+}
+
+#[test]
+fn test_short_clusters() {
+    let config = search(&["-iFU", "abc", "a.txt"]);
+    assert!(config.case_insensitive);
+    assert!(config.fixed_strings);
+    assert!(config.multiline);
+
+    let config = search(&["-Pie", "x+", "a.txt"]);
+    assert_eq!(config.syntax, Syntax::Perl);
+    assert_eq!(config.patterns, vec!["x+"]);
+    assert_eq!(config.files, vec!["a.txt"]);
+
+    let config = search(&["-iex+", "a.txt"]);
+    assert_eq!(config.patterns, vec!["x+"]);
+}
+
+#[test]
+fn test_long_options_values() {
+    let config = search(&["--match-limit=0", "--max-errors", "2", "abc", "a.txt"]);
+    assert_eq!(config.match_limit, None);
+    assert_eq!(config.max_errors, Some(2));
+
+    let config = search(&["--replace", "<$1>", "--replace=[$0]", "abc", "a.txt"]);
+    assert_eq!(config.replacement.as_deref(), Some("[$0]"));
+}
+
+#[test]
+fn test_repeated_patterns() {
+    let config = search(&["-e", "a", "--regexp=b", "-e-c", "a.txt", "b.txt"]);
+    assert_eq!(config.patterns, vec!["a", "b", "-c"]);
+    assert_eq!(config.files, vec!["a.txt", "b.txt"]);
+}
+
+#[test]
+fn test_end_of_options() {
+    let config = search(&["-i", "--", "-F", "--help"]);
+    assert_eq!(config.patterns, vec!["-F"]);
+    assert_eq!(config.files, vec!["--help"]);
+    assert!(!config.fixed_strings);
+
+    let config = search(&["a", "-"]);
+    assert_eq!(config.files, vec!["-"]);
+}
+
+#[test]
+fn test_help_and_version() {
+    assert_eq!(parse_args(["--help"]), Ok(Command::Help));
+    assert_eq!(parse_args(["-i", "--help", "--bogus"]), Ok(Command::Help));
+    assert_eq!(parse_args(["-V"]), Ok(Command::Version));
+    assert_eq!(parse_args(["--version"]), Ok(Command::Version));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        error(&["--bogus", "a", "b"]),
+        ArgsError::UnknownOption("--bogus".to_string())
+    );
+    assert_eq!(
+        error(&["-iz", "a", "b"]),
+        ArgsError::UnknownOption("-z".to_string())
+    );
+    assert_eq!(
+        error(&["a", "b", "-e"]),
+        ArgsError::MissingValue("-e".to_string())
+    );
+    assert_eq!(
+        error(&["a", "b", "--max-errors"]),
+        ArgsError::MissingValue("--max-errors".to_string())
+    );
+    assert_eq!(
+        error(&["--help=yes"]),
+        ArgsError::UnexpectedValue("--help".to_string())
+    );
+    assert_eq!(
+        error(&["--match-limit=lots", "a", "b"]),
+        ArgsError::InvalidValue {
+            option: "--match-limit".to_string(),
+            value: "lots".to_string(),
+        }
+    );
+    assert_eq!(error(&[]), ArgsError::MissingPattern);
+    assert_eq!(
+        error(&["-U", "--replace", "x", "a", "b"]),
+        ArgsError::IncompatibleOptions("--multiline".to_string(), "--replace".to_string())
+    );
+    assert_eq!(
+        error(&["--bogus"]).to_string(),
+        "unrecognized option '--bogus'"
+    );
+}
+
+#[test]
+fn test_filename_prefix() {
+    assert!(!search(&["a", "a.txt"]).prints_filenames());
+    assert!(search(&["a", "a.txt", "b.txt"]).prints_filenames());
+    assert!(search(&["-H", "a", "a.txt"]).prints_filenames());
+    assert!(!search(&["-h", "a", "a.txt", "b.txt"]).prints_filenames());
+    assert!(search(&["-hH", "a", "a.txt"]).prints_filenames());
+    assert_eq!(
+        search(&["--no-filename", "a", "a.txt"]).with_filename,
+        Some(false)
+    );
+}
+
+#[test]
+fn test_standard_input() {
+    let config = search(&["a"]);
+    assert_eq!(config.files, vec!["-"]);
+    assert!(!config.prints_filenames());
+
+    let config = search(&["--label", "input", "a", "-", "a.txt"]);
+    assert_eq!(config.label.as_deref(), Some("input"));
+    assert_eq!(config.files, vec!["-", "a.txt"]);
+    assert_eq!(search(&["--label=in", "a"]).label.as_deref(), Some("in"));
+}
+
+#[test]
+fn test_recursive() {
+    let config = search(&["-r", "a"]);
+    assert!(config.recursive);
+    assert!(!config.follow_links);
+    assert_eq!(config.files, vec!["."]);
+    assert!(config.prints_filenames());
+
+    let config = search(&["-R", "--max-depth=2", "a", "src"]);
+    assert!(config.follow_links);
+    assert_eq!(config.max_depth, Some(2));
+    assert!(!search(&["-rh", "a", "src"]).prints_filenames());
+}
+
+#[test]
+fn test_globs() {
+    let config = search(&["-r", "--include=*.rs", "--exclude", "*.min.js", "a"]);
+    assert_eq!(config.include.len(), 1);
+    assert!(config.include[0].matches("main.rs"));
+    assert!(config.exclude[0].matches("app.min.js"));
+
+    let config = search(&["-r", "--exclude-dir=target/", "a"]);
+    assert_eq!(config.exclude_dir[0].as_str(), "target");
+    assert_eq!(
+        error(&["--include=[z-a]", "a"]),
+        ArgsError::InvalidValue {
+            option: "--include".to_string(),
+            value: "[z-a]".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_ignore_options() {
+    let config = search(&["-r", "a"]);
+    assert!(!config.no_ignore);
+    assert!(!config.hidden);
+    let config = search(&["-r", "--no-ignore", "--hidden", "a"]);
+    assert!(config.no_ignore);
+    assert!(config.hidden);
+}
+
+#[test]
+fn test_threads() {
+    assert_eq!(search(&["a"]).threads, None);
+    assert!(search(&["a"]).thread_count() >= 1);
+    assert_eq!(search(&["-j", "4", "a"]).threads, Some(4));
+    assert_eq!(search(&["--threads=1", "a"]).thread_count(), 1);
+    assert_eq!(
+        error(&["-j0", "a"]),
+        ArgsError::InvalidValue {
+            option: "-j".to_string(),
+            value: "0".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_invert_match() {
+    assert!(!search(&["a"]).invert_match);
+    assert!(search(&["-v", "a"]).invert_match);
+    assert!(search(&["--invert-match", "a"]).invert_match);
+    assert_eq!(
+        error(&["-vU", "a"]),
+        ArgsError::IncompatibleOptions("--invert-match".to_string(), "--multiline".to_string())
+    );
+}
+
+#[test]
+fn test_context() {
+    let config = search(&["-A", "2", "-B1", "a"]);
+    assert_eq!((config.before_context, config.after_context), (1, 2));
+    let config = search(&["--context=3", "--after-context", "0", "a"]);
+    assert_eq!((config.before_context, config.after_context), (3, 0));
+    assert_eq!(
+        error(&["-C", "x", "a"]),
+        ArgsError::InvalidValue {
+            option: "-C".to_string(),
+            value: "x".to_string(),
+        }
+    );
+    assert_eq!(
+        error(&["-U", "-B1", "a"]),
+        ArgsError::IncompatibleOptions("--context".to_string(), "--multiline".to_string())
+    );
+}
+
+#[test]
+fn test_reports() {
+    assert_eq!(search(&["a"]).report, Report::Lines);
+    assert_eq!(search(&["-c", "a"]).report, Report::Count);
+    assert_eq!(search(&["-cl", "a"]).report, Report::FilesWithMatches);
+    assert_eq!(
+        search(&["--files-without-match", "a"]).report,
+        Report::FilesWithoutMatch
+    );
+}
+
+#[test]
+fn test_positions() {
+    let config = search(&["-nb", "--column", "-o", "a"]);
+    assert!(config.line_number && config.byte_offset && config.column && config.only_matching);
+    assert!(!config.vimgrep);
+    assert!(!search(&["a"]).prints_filenames());
+    assert!(search(&["--vimgrep", "a"]).prints_filenames());
+    assert!(search(&["-nU", "a"]).line_number);
+    assert!(search(&["-U", "--vimgrep", "a"]).vimgrep);
+    assert!(search(&["-Ub", "a"]).byte_offset);
+}
+
+#[test]
+fn test_color() {
+    assert_eq!(search(&["a"]).color, ColorChoice::Auto);
+    assert_eq!(search(&["--color=always", "a"]).color, ColorChoice::Always);
+    assert_eq!(search(&["--color", "never", "a"]).color, ColorChoice::Never);
+    assert_eq!(
+        error(&["--color=yes", "a"]),
+        ArgsError::InvalidValue {
+            option: "--color".to_string(),
+            value: "yes".to_string(),
+        }
+    );
+}
@@ -0,0 +1,90 @@
+use grep_rustico::fuzzy::{self, Myers};
+use grep_rustico::match_result::{Edit, EditKind};
+use grep_rustico::nfa::Nfa;
+use grep_rustico::regex::Regex;
+
+fn edit(kind: EditKind, position: usize) -> Edit {
+    Edit { kind, position }
+}
+
+#[test]
+fn test_exact_match_is_preferred() {
+    let regex = Regex::new("hello").unwrap();
+    let result = regex.find_fuzzy("hell hello", 1).unwrap();
+    assert_eq!(result.range(), (5, 10));
+    assert_eq!(result.cost, 0);
+    assert!(result.edits.is_empty());
+}
+
+#[test]
+fn test_edits() {
+    let regex = Regex::new("color").unwrap();
+
+    let result = regex.find_fuzzy("the colour red", 1).unwrap();
+    assert_eq!(result.matched, "colour");
+    assert_eq!(result.edits, vec![edit(EditKind::Insertion, 8)]);
+
+    let result = regex.find_fuzzy("the colr red", 1).unwrap();
+    assert_eq!(result.matched, "colr");
+    assert_eq!(result.edits, vec![edit(EditKind::Deletion, 7)]);
+
+    let result = regex.find_fuzzy("the celor red", 1).unwrap();
+    assert_eq!(result.matched, "celor");
+    assert_eq!(result.edits, vec![edit(EditKind::Substitution, 5)]);
+
+    assert!(regex.find_fuzzy("the cl red", 1).is_none());
+    assert_eq!(regex.find_fuzzy("the cl red", 3).unwrap().cost, 2);
+}
+
+#[test]
+fn test_general_patterns() {
+    let regex = Regex::new("[0-9]{3}-[0-9]{4}").unwrap();
+    let result = regex.find_fuzzy("call 555-l234 now", 1).unwrap();
+    assert_eq!(result.matched, "555-l234");
+    assert_eq!(result.edits, vec![edit(EditKind::Substitution, 9)]);
+
+    let regex = Regex::new("fo+bar").unwrap();
+    let result = regex.find_fuzzy("a foooobr b", 1).unwrap();
+    assert_eq!(result.matched, "foooobr");
+    assert_eq!(result.edits, vec![edit(EditKind::Deletion, 8)]);
+
+    let regex = Regex::new("^(cat|dog)s$").unwrap();
+    assert_eq!(regex.find_fuzzy("dots", 1).unwrap().cost, 1);
+    assert!(regex.find_fuzzy("a dots", 2).is_none());
+    assert_eq!(regex.find_fuzzy("a dots", 3).unwrap().range(), (0, 6));
+}
+
+#[test]
+fn test_unicode() {
+    let regex = Regex::new("café").unwrap();
+    let result = regex.find_fuzzy("un cafe noir", 1).unwrap();
+    assert_eq!(result.matched, "cafe");
+    assert_eq!(result.edits, vec![edit(EditKind::Substitution, 6)]);
+
+    let regex = Regex::new("caf[ée]").unwrap();
+    assert_eq!(regex.find_fuzzy("un cafè", 1).unwrap().matched, "cafè");
+}
+
+#[test]
+fn test_bit_parallel_same_as_automaton() {
+    let patterns = ["abc", "hello", "aab", "ab", "mississippi"];
+    let values = [
+        "", "a", "xbc", "abbc", "acb", "hallo help", "aaab", "ba", "misisipi", "ab ab", "zzzz",
+    ];
+    for pattern in patterns {
+        let regex = Regex::new(pattern).unwrap();
+        let nfa = Nfa::compile(std::slice::from_ref(&regex));
+        let myers = Myers::new(pattern).unwrap();
+        for value in values {
+            for max_errors in 0..=3 {
+                let expected = fuzzy::search(&nfa, value.as_bytes(), max_errors);
+                let found = myers.find(value.as_bytes(), max_errors);
+                assert_eq!(
+                    found.as_ref().map(|found| (found.start, found.end, found.cost())),
+                    expected.as_ref().map(|found| (found.start, found.end, found.cost())),
+                    "{pattern} in {value:?} with {max_errors} errors"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use grep_rustico::error::RegexError;
+use grep_rustico::glob::Glob;
+
+fn glob(pattern: &str) -> Glob {
+    Glob::new(pattern).unwrap()
+}
+
+#[test]
+fn test_wildcards() {
+    assert!(glob("*.rs").matches("main.rs"));
+    assert!(glob("*.rs").matches(".rs"));
+    assert!(!glob("*.rs").matches("main.rs.bak"));
+    assert!(!glob("*.rs").matches("src/main.rs"));
+    assert!(glob("?.txt").matches("a.txt"));
+    assert!(!glob("?.txt").matches("ab.txt"));
+    assert!(!glob("a?b").matches("a/b"));
+    assert!(glob("*.min.js").matches("app.min.js"));
+    assert!(glob("a*b*c").matches("abbbc"));
+    assert!(!glob("a*b*c").matches("abbb"));
+}
+
+#[test]
+fn test_brackets() {
+    assert!(glob("file[0-9].txt").matches("file7.txt"));
+    assert!(!glob("file[0-9].txt").matches("filex.txt"));
+    assert!(glob("[!a-c]*").matches("dog"));
+    assert!(!glob("[!a-c]*").matches("cat"));
+    assert!(glob("[^a-c]*").matches("dog"));
+    assert!(glob("[]x]").matches("]"));
+    assert!(glob("a[").matches("a["));
+    assert_eq!(Glob::new("[z-a]"), Err(RegexError::InvalidBracketRange));
+}
+
+#[test]
+fn test_double_star() {
+    assert!(glob("**/test.rs").matches("test.rs"));
+    assert!(glob("**/test.rs").matches("a/b/test.rs"));
+    assert!(glob("src/**/*.rs").matches("src/main.rs"));
+    assert!(glob("src/**/*.rs").matches("src/a/b/main.rs"));
+    assert!(!glob("src/**/*.rs").matches("lib/main.rs"));
+    assert!(glob("target/**").matches("target/debug/build"));
+    assert!(!glob("target/**").matches("target"));
+    assert!(glob("a**b").matches("axxb"));
+    assert!(!glob("a**b").matches("ax/xb"));
+}
+
+#[test]
+fn test_alternatives() {
+    assert!(glob("*.{rs,toml}").matches("Cargo.toml"));
+    assert!(glob("*.{rs,toml}").matches("lib.rs"));
+    assert!(!glob("*.{rs,toml}").matches("lib.py"));
+    assert!(glob("a{b,c{d,e}}f").matches("acef"));
+    assert!(glob("{,x}y").matches("y"));
+    assert!(glob("{a").matches("{a"));
+    assert!(glob("\\*").matches("*"));
+    assert!(!glob("\\*").matches("a"));
+}
+
+#[test]
+fn test_paths() {
+    assert!(glob("*.rs").is_match("src/main.rs"));
+    assert!(!glob("src/*.rs").is_match("main.rs"));
+    assert!(glob("src/*.rs").is_match("./src/main.rs"));
+    assert!(!glob("src/*.rs").is_match("lib/src/main.rs"));
+    assert_eq!(glob("{a,b}/*").as_str(), "{a,b}/*");
+}
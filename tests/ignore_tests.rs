@@ -0,0 +1,132 @@
+use grep_rustico::ignore::{self, IgnoreFile};
+use grep_rustico::walker::Walker;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn ignored(content: &str, path: &str, is_dir: bool) -> bool {
+    let file = Arc::new(IgnoreFile::parse(content, Path::new("root")));
+    ignore::is_ignored(&[file], &Path::new("root").join(path), is_dir)
+}
+
+#[test]
+fn test_names_and_paths() {
+    assert!(ignored("*.log", "debug.log", false));
+    assert!(ignored("*.log", "a/b/debug.log", false));
+    assert!(!ignored("*.log", "debug.txt", false));
+    assert!(ignored("/todo.txt", "todo.txt", false));
+    assert!(!ignored("/todo.txt", "a/todo.txt", false));
+    assert!(ignored("doc/*.txt", "doc/a.txt", false));
+    assert!(!ignored("doc/*.txt", "doc/x/a.txt", false));
+    assert!(!ignored("doc/*.txt", "a/doc/a.txt", false));
+    assert!(ignored("**/doc/*.txt", "a/doc/a.txt", false));
+    assert!(ignored("a/**/b", "a/x/y/b", false));
+}
+
+#[test]
+fn test_comments_and_escapes() {
+    assert!(!ignored("# comment\n\n", "# comment", false));
+    assert!(ignored("\\#hash", "#hash", false));
+    assert!(ignored("\\!bang", "!bang", false));
+    assert!(ignored("trailing   ", "trailing", false));
+    assert!(ignored("space\\ ", "space ", false));
+}
+
+#[test]
+fn test_directories_and_negation() {
+    assert!(ignored("build/", "build", true));
+    assert!(!ignored("build/", "build", false));
+    assert!(ignored("build/", "a/build", true));
+    assert!(!ignored("*.log\n!keep.log", "keep.log", false));
+    assert!(ignored("!keep.log\n*.log", "keep.log", false));
+
+    let root = Arc::new(IgnoreFile::parse("*.log", Path::new("root")));
+    let nested = Arc::new(IgnoreFile::parse("!*.log", Path::new("root/sub")));
+    let files = [root, nested];
+    assert!(!ignore::is_ignored(
+        &files,
+        Path::new("root/sub/a.log"),
+        false
+    ));
+    assert!(ignore::is_ignored(&files, Path::new("root/a.log"), false));
+}
+
+/// Creates the files with their content under a new directory for the test in the temporary directory.
+fn temp_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("grep-rustico-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    root
+}
+
+fn walk(walker: &Walker, root: &Path) -> Vec<String> {
+    walker
+        .walk(root)
+        .map(|entry| {
+            let path = entry.unwrap();
+            path.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_walk_hierarchy() {
+    let root = temp_tree(
+        "ignore",
+        &[
+            (".git/info/exclude", "secret.txt\n"),
+            (".gitignore", "target/\n*.tmp\n"),
+            ("main.rs", ""),
+            ("secret.txt", ""),
+            ("a.tmp", ""),
+            ("target/out.rs", ""),
+            ("sub/.ignore", "!b.tmp\n/local.rs\n"),
+            ("sub/b.tmp", ""),
+            ("sub/local.rs", ""),
+            ("sub/deep/local.rs", ""),
+            (".env", ""),
+        ],
+    );
+    assert_eq!(
+        walk(&Walker::new(), &root),
+        vec!["main.rs", "sub/b.tmp", "sub/deep/local.rs"]
+    );
+
+    let mut walker = Walker::new();
+    walker.hidden(true);
+    assert_eq!(
+        walk(&walker, &root),
+        vec![
+            ".env",
+            ".git/info/exclude",
+            ".gitignore",
+            "main.rs",
+            "sub/.ignore",
+            "sub/b.tmp",
+            "sub/deep/local.rs"
+        ]
+    );
+
+    let mut walker = Walker::new();
+    walker.ignore_files(false);
+    assert_eq!(
+        walk(&walker, &root),
+        vec![
+            "a.tmp",
+            "main.rs",
+            "secret.txt",
+            "sub/b.tmp",
+            "sub/deep/local.rs",
+            "sub/local.rs",
+            "target/out.rs"
+        ]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
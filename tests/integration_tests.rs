@@ -1,15 +1,31 @@
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
 
-fn run_test(regex: &str, expected_output: &str) {
-    let output = Command::new("cargo")
-        .arg("run")
-        .arg(regex)
-        .arg("texts/test.txt")
+/// Runs the program with the arguments, keeping its output as raw bytes.
+fn run(args: &[&str]) -> Output {
+    Command::new("cargo")
+        .args(["run", "--"])
+        .args(args)
         .output()
-        .expect("Failed to execute command");
+        .expect("Failed to execute command")
+}
+
+/// Runs the program with the arguments and without colors, so its output can be compared as is.
+fn run_uncolored(args: &[&str]) -> Output {
+    run(&[&["--color=never"], args].concat())
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn run_test(regex: &str, expected_output: &str) {
+    run_with_args(&[regex, "texts/test.txt"], expected_output);
+}
 
-    let output_str = String::from_utf8(output.stdout).unwrap();
-    assert_eq!(output_str.trim(), expected_output);
+fn run_with_args(args: &[&str], expected_output: &str) {
+    let output = run_uncolored(args);
+    assert_eq!(stdout(&output).trim(), expected_output);
 }
 
 #[test]
@@ -37,6 +53,29 @@ fn test_or_plus() {
     run_test("abc|d+f", "df\nhola abcdefg");
 }
 
+#[test]
+fn test_matches_are_highlighted() {
+    let output = run(&["-Hn", "hola", "texts/test.txt"]);
+    assert_eq!(
+        stdout(&output),
+        "texts/test.txt:8:\x1b[31mhola\x1b[0m abcdefg\ntexts/test.txt:11:\x1b[31mhola\x1b[0m mundo\n"
+    );
+}
+
+#[test]
+fn test_color() {
+    let output = run_uncolored(&["-Hn", "--color=always", "mundo", "texts/test.txt"]);
+    assert_eq!(
+        stdout(&output),
+        "\x1b[35mtexts/test.txt\x1b[0m:\x1b[32m11\x1b[0m:hola \x1b[31mmundo\x1b[0m\n"
+    );
+    let output = run_uncolored(&["-Hn", "mundo", "texts/test.txt"]);
+    assert_eq!(stdout(&output), "texts/test.txt:11:hola mundo\n");
+    let output = run_uncolored(&["--color=sometimes", "mundo", "texts/test.txt"]);
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.status.code(), Some(2));
+}
+
 #[test]
 fn test_bracket_spaces() {
     run_test("la [aeiou] es una vocal", "la a es una vocal");
@@ -74,3 +113,426 @@ fn test_character_class_upper() {
 fn test_dollar() {
     run_test("es el fin$", "es el fin");
 }
+
+#[test]
+fn test_fixed_strings() {
+    run_with_args(&["-F", "a.b[0]*", "texts/literals.txt"], "a.b[0]*");
+}
+
+#[test]
+fn test_fixed_strings_multiple() {
+    run_with_args(
+        &["--fixed-strings", "$5.00 (\na.b", "texts/literals.txt"],
+        "a.b[0]*\nprice: $5.00 (approx)\na.b",
+    );
+}
+
+#[test]
+fn test_basic_regexp() {
+    run_with_args(&["-G", "a\\{2,3\\}b\\|df", "texts/test.txt"], "df");
+}
+
+#[test]
+fn test_extended_groups() {
+    run_with_args(&["-E", "^(ab|ac)d$", "texts/test.txt"], "abd\nacd");
+}
+
+#[test]
+fn test_perl_regexp() {
+    run_with_args(&["-P", "^(?:ab|ac)\\x64$", "texts/test.txt"], "abd\nacd");
+}
+
+#[test]
+fn test_replace() {
+    run_with_args(
+        &["--replace", "<$1>", "a([bc])d", "texts/test.txt"],
+        "<b>\n<c>",
+    );
+    run_with_args(
+        &["--replace=[$0]", "z+", "texts/test.txt"],
+        "ab[zzz]cd\nla [z] no es una vocal",
+    );
+    run_with_args(
+        &[
+            "-e",
+            "a([bc])d",
+            "-e",
+            "(z+)",
+            "--replace=<$1|$2>",
+            "texts/test.txt",
+        ],
+        "ab<|zzz>cd\n<b|>\n<c|>\nla <|z> no es una vocal",
+    );
+}
+
+#[test]
+fn test_invalid_utf8_file() {
+    run_with_args(&["au lait", "texts/latin1.txt"], "caf\u{fffd} au lait");
+    run_with_args(
+        &["binary", "texts/latin1.txt"],
+        "\u{fffd}\u{fffd} binary \u{fffd} data",
+    );
+}
+
+#[test]
+fn test_match_limit() {
+    run_with_args(
+        &[
+            "--match-limit",
+            "1000",
+            "(a|a)*[xy]",
+            "texts/backtracking.txt",
+        ],
+        "aay",
+    );
+}
+
+#[test]
+fn test_multiline() {
+    run_with_args(
+        &[
+            "-U",
+            "fn [a-z_]+\\(\\n[[:space:]]*self",
+            "texts/multiline.txt",
+        ],
+        "fn distance(\n        self,",
+    );
+    run_with_args(&["-U", "i32,\\n|x", "texts/multiline.txt"], "x: i32,");
+    run_with_args(
+        &["-U", "-E", "\\{$", "texts/multiline.txt"],
+        "impl Point {\n    ) -> Point {\n    ) -> f64 {\n    fn norm(self) -> f64 {",
+    );
+}
+
+#[test]
+fn test_max_errors() {
+    run_with_args(
+        &["--max-errors", "1", "color", "texts/fuzzy.txt"],
+        "the colour red\nthe colr red\ncolor",
+    );
+    run_with_args(
+        &["--max-errors=0", "colou?r", "texts/fuzzy.txt"],
+        "the colour red\ncolor",
+    );
+    run_with_args(
+        &["-F", "--max-errors", "1", "colr", "texts/fuzzy.txt"],
+        "the colour red\nthe colr red\ncolor",
+    );
+}
+
+#[test]
+fn test_options_parser() {
+    run_with_args(&["-i", "PASCAL", "texts/test.txt"], "PascalCase");
+    run_with_args(
+        &["-e", "^df$", "-e", "mundo", "texts/test.txt"],
+        "df\nhola mundo",
+    );
+    run_with_args(
+        &["-iFe", "HOLA", "texts/test.txt"],
+        "hola abcdefg\nhola mundo",
+    );
+    run_with_args(&["--", "-", "texts/test.txt"], "");
+    run_with_args(&["--version"], "grep-rustico 0.1.0");
+}
+
+#[test]
+fn test_multiple_files() {
+    run_with_args(
+        &["hola", "texts/test.txt", "texts/fuzzy.txt", "texts/test.txt"],
+        "texts/test.txt:hola abcdefg\ntexts/test.txt:hola mundo\ntexts/test.txt:hola abcdefg\ntexts/test.txt:hola mundo",
+    );
+    run_with_args(&["-h", "^df", "texts/test.txt", "texts/test.txt"], "df\ndf");
+    run_with_args(&["-H", "^df", "texts/test.txt"], "texts/test.txt:df");
+}
+
+#[test]
+fn test_missing_file_doesnt_stop_search() {
+    let output = run_uncolored(&["^df", "texts/missing.txt", "texts/test.txt"]);
+    assert_eq!(stdout(&output), "texts/test.txt:df\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("grep: texts/missing.txt: "));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+fn run_with_stdin<I: AsRef<[u8]>>(args: &[&str], input: I) -> String {
+    let mut child = Command::new("cargo")
+        .args(["run", "--", "--color=never"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_ref())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    stdout(&output)
+}
+
+#[test]
+fn test_standard_input() {
+    assert_eq!(run_with_stdin(&["^b"], "abc\nbcd\ncde\n"), "bcd\n");
+    assert_eq!(
+        run_with_stdin(&["-H", "c$", "-"], "abc\nbcd\n"),
+        "(standard input):abc\n"
+    );
+    assert_eq!(
+        run_with_stdin(&["--label=in", "^d", "-", "texts/test.txt"], "abc\ndef\n"),
+        "in:def\ntexts/test.txt:df\n"
+    );
+}
+
+#[test]
+fn test_recursive() {
+    run_with_args(
+        &["-r", "hola", "texts/tree"],
+        "texts/tree/a.txt:hola arbol\ntexts/tree/sub/b.txt:hola rama\ntexts/tree/sub/d.rs:fn hola() {}\ntexts/tree/sub/deep/c.txt:hola hoja",
+    );
+    run_with_args(
+        &["-r", "--max-depth=1", "hola", "texts/tree"],
+        "texts/tree/a.txt:hola arbol",
+    );
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "hola", "texts/tree"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("grep: texts/tree: Is a directory"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_globs() {
+    run_with_args(
+        &["-r", "--include=*.rs", "hola", "texts/tree"],
+        "texts/tree/sub/d.rs:fn hola() {}",
+    );
+    run_with_args(
+        &[
+            "-r",
+            "--exclude=*.rs",
+            "--exclude-dir=deep",
+            "hola",
+            "texts/tree",
+        ],
+        "texts/tree/a.txt:hola arbol\ntexts/tree/sub/b.txt:hola rama",
+    );
+}
+
+#[test]
+fn test_ignore_files() {
+    run_with_args(
+        &["-r", "hola", "texts/ignored"],
+        "texts/ignored/a.txt:hola\ntexts/ignored/keep.log:hola keep",
+    );
+    run_with_args(
+        &["-r", "--no-ignore", "hola", "texts/ignored"],
+        "texts/ignored/a.txt:hola\ntexts/ignored/build/out.txt:hola build\ntexts/ignored/debug.log:hola log\ntexts/ignored/keep.log:hola keep",
+    );
+    run_with_args(
+        &["-r", "--hidden", "oculto", "texts/tree"],
+        "texts/tree/.hidden.txt:hola oculto",
+    );
+    run_with_args(&["-r", "oculto", "texts/tree"], "");
+}
+
+#[test]
+fn test_threads_keep_order() {
+    let expected = "texts/tree/a.txt:hola arbol\ntexts/tree/sub/b.txt:hola rama\ntexts/tree/sub/d.rs:fn hola() {}\ntexts/tree/sub/deep/c.txt:hola hoja";
+    for threads in ["1", "2", "8"] {
+        run_with_args(&["-j", threads, "-r", "hola", "texts/tree"], expected);
+    }
+    let output = run_uncolored(&["-j8", "^df", "texts/missing.txt", "texts/test.txt"]);
+    assert_eq!(stdout(&output), "texts/test.txt:df\n");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_invert_match() {
+    run_with_args(&["-v", "a|e", "texts/test.txt"], "df");
+    run_with_args(
+        &["-v", "-e", "c", "-e", "z", "texts/test.txt"],
+        "abd\ndf\n1 es un numero\nhola mundo\nes el fin",
+    );
+    run_with_args(&["-vF", "o", "texts/fuzzy.txt"], "COLOR");
+    run_with_args(
+        &["-v", "--max-errors=1", "color", "texts/fuzzy.txt"],
+        "no match here\nCOLOR",
+    );
+    run_with_args(&["-vH", "--replace=x", "[a-z]", "texts/test.txt"], "");
+}
+
+#[test]
+fn test_count() {
+    run_with_args(&["-c", "hola", "texts/test.txt"], "2");
+    run_with_args(&["-cv", "hola", "texts/test.txt"], "11");
+    run_with_args(
+        &["-c", "hola", "texts/test.txt", "texts/fuzzy.txt"],
+        "texts/test.txt:2\ntexts/fuzzy.txt:0",
+    );
+    run_with_args(&["-c", "--max-errors=1", "color", "texts/fuzzy.txt"], "3");
+    run_with_args(&["-cU", "fn [a-z]+\\(", "texts/multiline.txt"], "3");
+}
+
+#[test]
+fn test_reports_are_not_colored() {
+    let files = ["texts/test.txt", "texts/fuzzy.txt"];
+    let output = run(&[&["--color=always", "-c", "hola"], &files[..]].concat());
+    assert_eq!(output.stdout, b"texts/test.txt:2\ntexts/fuzzy.txt:0\n");
+    let output = run(&[&["--color=always", "-l", "hola"], &files[..]].concat());
+    assert_eq!(output.stdout, b"texts/test.txt\n");
+    let output = run(&[&["--color=always", "-L", "hola"], &files[..]].concat());
+    assert_eq!(output.stdout, b"texts/fuzzy.txt\n");
+    let output = run(&[
+        "--color=always",
+        "-cHU",
+        "fn [a-z]+\\(",
+        "texts/multiline.txt",
+    ]);
+    assert_eq!(output.stdout, b"texts/multiline.txt:3\n");
+}
+
+#[test]
+fn test_line_numbers_and_offsets() {
+    run_with_args(
+        &["-n", "hola", "texts/test.txt"],
+        "8:hola abcdefg\n11:hola mundo",
+    );
+    run_with_args(
+        &["-b", "^ab", "texts/test.txt"],
+        "0:abecd\n6:abzzzcd\n14:abd",
+    );
+    run_with_args(&["-n", "--column", "es", "texts/test.txt"], "6:6:la a es una vocal\n7:9:la z no es una vocal\n9:3:1 es un numero\n10:18:el caracter a no es un simbolo\n13:1:es el fin");
+    run_with_args(
+        &["-nv", "a", "texts/test.txt"],
+        "5:df\n9:1 es un numero\n13:es el fin",
+    );
+    assert_eq!(run_with_stdin(&["-nb", "ab"], "x\r\nab\n"), "2:3:ab\n");
+    run_with_args(&["-Ub", "d\\nacd", "texts/test.txt"], "14:abd\n18:acd");
+}
+
+#[test]
+fn test_multiline_positions() {
+    run_with_args(
+        &["-Un", "fn [a-z]+\\(\\n", "texts/multiline.txt"],
+        "2:    fn new(\n5:    fn distance(",
+    );
+    run_with_args(
+        &[
+            "-Un",
+            "--column",
+            "i32,\\n[[:space:]]+\\)|Point",
+            "texts/multiline.txt",
+        ],
+        "1:6:impl Point {\n3:12:        x: i32,\n4:1:    ) -> Point {\n7:16:        other: Point,",
+    );
+    run_with_args(
+        &[
+            "-Uonb",
+            "x: i32,\\n[[:space:]]+\\)|norm",
+            "texts/multiline.txt",
+        ],
+        "3:33:x: i32,\n    )\n9:133:norm",
+    );
+    run_with_args(
+        &["-U", "--vimgrep", "\\(\\n[[:space:]]+self|f64", "texts/multiline.txt"],
+        "texts/multiline.txt:5:16:    fn distance(\ntexts/multiline.txt:8:10:    ) -> f64 {\ntexts/multiline.txt:9:22:    fn norm(self) -> f64 {",
+    );
+}
+
+#[test]
+fn test_only_matching() {
+    run_with_args(&["-o", "z+", "texts/test.txt"], "zzz\nz");
+    run_with_args(&["-ob", "hola", "texts/test.txt"], "64:hola\n123:hola");
+    run_with_args(
+        &["-on", "--column", "z", "texts/test.txt"],
+        "2:3:z\n2:4:z\n2:5:z\n7:4:z",
+    );
+    run_with_args(
+        &["-o", "--replace=<$0>", "^ab", "texts/test.txt"],
+        "<ab>\n<ab>\n<ab>",
+    );
+    run_with_args(&["-ov", "a", "texts/test.txt"], "");
+    assert_eq!(
+        run_with_stdin(&["-ob", "foo"], b"a\xff\xffbfoo\n"),
+        "4:foo\n"
+    );
+    assert_eq!(
+        run_with_stdin(&["--vimgrep", "foo"], b"\xfe foo\n"),
+        "(standard input):1:3:\u{FFFD} foo\n"
+    );
+}
+
+#[test]
+fn test_vimgrep() {
+    run_with_args(
+        &["--vimgrep", "hola|mundo", "texts/test.txt"],
+        "texts/test.txt:8:1:hola abcdefg\ntexts/test.txt:11:1:hola mundo\ntexts/test.txt:11:6:hola mundo",
+    );
+    run_with_args(
+        &["--vimgrep", "--max-errors=1", "mundi", "texts/test.txt"],
+        "texts/test.txt:11:6:hola mundo",
+    );
+    for color in ["--color=auto", "--color=always"] {
+        let output = run(&[color, "--vimgrep", "mundo", "texts/test.txt"]);
+        assert_eq!(output.stdout, b"texts/test.txt:11:6:hola mundo\n");
+        let output = run(&[color, "-U", "--vimgrep", "f64", "texts/multiline.txt"]);
+        assert_eq!(
+            output.stdout,
+            b"texts/multiline.txt:8:10:    ) -> f64 {\ntexts/multiline.txt:9:22:    fn norm(self) -> f64 {\n"
+        );
+    }
+}
+
+#[test]
+fn test_context() {
+    run_with_args(
+        &["-A1", "hola", "texts/test.txt"],
+        "hola abcdefg\n1 es un numero\n--\nhola mundo\nPascalCase",
+    );
+    run_with_args(
+        &["-n", "-C1", "mundo", "texts/test.txt"],
+        "10-el caracter a no es un simbolo\n11:hola mundo\n12-PascalCase",
+    );
+    run_with_args(
+        &["-nv", "-A1", "a", "texts/test.txt"],
+        "5:df\n6-la a es una vocal\n--\n9:1 es un numero\n10-el caracter a no es un simbolo\n--\n13:es el fin",
+    );
+    run_with_args(
+        &["-Hb", "--column", "-B2", "^acd", "texts/test.txt"],
+        "texts/test.txt-6-abzzzcd\ntexts/test.txt-14-abd\ntexts/test.txt:1:18:acd",
+    );
+    run_with_args(&["-c", "-C2", "hola", "texts/test.txt"], "2");
+    run_with_args(&["-o", "-C2", "hola", "texts/test.txt"], "hola\nhola");
+}
+
+#[test]
+fn test_files_with_and_without_match() {
+    let files = ["texts/test.txt", "texts/fuzzy.txt", "texts/fruits.txt"];
+    let args = |options: &[&'static str]| -> Vec<&'static str> {
+        options.iter().chain(files.iter()).copied().collect()
+    };
+    run_with_args(&args(&["-l", "color"]), "texts/fuzzy.txt");
+    run_with_args(&args(&["-L", "color"]), "texts/test.txt\ntexts/fruits.txt");
+    run_with_args(
+        &args(&["-lv", "^[a-z ]*$"]),
+        "texts/test.txt\ntexts/fuzzy.txt\ntexts/fruits.txt",
+    );
+    run_with_args(
+        &["-r", "-l", "hola", "texts/tree"],
+        "texts/tree/a.txt\ntexts/tree/sub/b.txt\ntexts/tree/sub/d.rs\ntexts/tree/sub/deep/c.txt",
+    );
+
+    // The search stops at the first match, so the line that exceeds the match limit isn't searched.
+    let output = run_uncolored(&[
+        "-l",
+        "--match-limit=1000",
+        "(a|a)*[xy]|short",
+        "texts/backtracking.txt",
+    ]);
+    assert_eq!(stdout(&output), "texts/backtracking.txt\n");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("match limit exceeded"));
+}
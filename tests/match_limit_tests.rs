@@ -0,0 +1,89 @@
+use grep_rustico::bytes;
+use grep_rustico::error::RegexError;
+use grep_rustico::file_handler::{FileHandler, Report};
+use grep_rustico::matcher::Matcher;
+use grep_rustico::regex::{ParseOptions, Regex};
+use grep_rustico::syntax::Syntax;
+use std::io::Cursor;
+
+const PATHOLOGICAL: &str = "(a|a)*[xy]";
+
+fn haystack() -> String {
+    "a".repeat(40)
+}
+
+#[test]
+fn test_match_limit_exceeded() {
+    let mut regex = Regex::new(PATHOLOGICAL).unwrap();
+    regex.set_match_limit(Some(10_000));
+    assert_eq!(
+        regex.match_expression(&haystack()).unwrap_err(),
+        RegexError::MatchLimitExceeded
+    );
+    assert_eq!(
+        regex.match_expression("aay").unwrap().unwrap().matched,
+        "aay"
+    );
+}
+
+#[test]
+fn test_fallback_to_linear_engine() {
+    let mut regex = Regex::new(PATHOLOGICAL).unwrap();
+    regex.set_match_limit(Some(10_000));
+    assert!(regex.find_at(&haystack(), 0).is_none());
+
+    let value = format!("{} ay", haystack());
+    let found = regex.find_at(&value, 0).unwrap();
+    assert_eq!(found.range(), (41, 43));
+    assert!(found.captures.is_empty());
+    assert_eq!(regex.search_linear(value.as_bytes(), 42), Some((42, 43)));
+
+    let regex = bytes::Regex::new(PATHOLOGICAL).unwrap();
+    assert!(!regex.is_match(haystack().as_bytes()));
+}
+
+#[test]
+fn test_linear_engine_same_match() {
+    let regex = Regex::new("^(ab|a)(bc|c)?|x*").unwrap();
+    for value in ["abc", "ac", "zz", "abx"] {
+        let found = regex.try_search(value.as_bytes(), 0).unwrap().unwrap();
+        assert_eq!(
+            regex.search_linear(value.as_bytes(), 0),
+            Some((found.start, found.end))
+        );
+    }
+}
+
+#[test]
+fn test_no_match_limit() {
+    let mut regex = Regex::new("(a|a)*[xy]").unwrap();
+    regex.set_match_limit(None);
+    assert!(regex.match_expression("aaaaaaaaaaay").unwrap().is_some());
+}
+
+#[test]
+fn test_multiline_buffer_limit() {
+    let options = ParseOptions {
+        multiline: true,
+        dot_matches_newline: false,
+        ..ParseOptions::default()
+    };
+    let matcher = Matcher::with_options(["ERROR[^Z]*Z"], Syntax::Extended, options).unwrap();
+    let search = |input: String| {
+        let mut handler = FileHandler::from_reader(Cursor::new(input), "big")
+            .with_report(Report::Count)
+            .with_buffer_limit(1 << 18)
+            .buffered();
+        let result = handler.process_multiline(&matcher);
+        (result, String::from_utf8(handler.take_output()).unwrap())
+    };
+
+    let unclosed = format!("ERROR {}", "abc def\n".repeat(250_000));
+    let (result, output) = search(unclosed);
+    assert_eq!(result.unwrap_err(), RegexError::BufferLimitExceeded);
+    assert_eq!(output, "");
+
+    let (result, output) = search("ERROR abc\ndef Z\n".repeat(125_000));
+    assert_eq!(result, Ok(()));
+    assert_eq!(output, "250000\n");
+}
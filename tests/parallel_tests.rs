@@ -0,0 +1,68 @@
+use grep_rustico::matcher::Matcher;
+use grep_rustico::parallel::run_ordered;
+use grep_rustico::regex::Regex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_shared_between_threads() {
+    assert_send_sync::<Regex>();
+    assert_send_sync::<Matcher>();
+
+    let regex = Regex::new("[0-9]+ (apples|pears)").unwrap();
+    thread::scope(|scope| {
+        for i in 0..4 {
+            let regex = &regex;
+            scope.spawn(move || {
+                let value = format!("{} apples", i);
+                assert_eq!(
+                    regex.match_expression(&value).unwrap().unwrap().matched,
+                    value
+                );
+            });
+        }
+    });
+}
+
+#[test]
+fn test_results_in_order() {
+    for threads in [1, 2, 4, 8] {
+        let mut results = vec![];
+        run_ordered(
+            threads,
+            0..50u64,
+            |job| {
+                // The first jobs take the longest, so their results come last.
+                thread::sleep(Duration::from_micros((50 - job) * 50));
+                job * 2
+            },
+            |result| results.push(result),
+        );
+        assert_eq!(results, (0..50).map(|job| job * 2).collect::<Vec<u64>>());
+    }
+}
+
+#[test]
+fn test_every_job_runs_once() {
+    let runs = AtomicUsize::new(0);
+    let mut count = 0;
+    run_ordered(
+        3,
+        (0..200).map(|job| job % 7),
+        |job| {
+            runs.fetch_add(1, Ordering::Relaxed);
+            // A few slow jobs leave their thread's queue to be stolen by the others.
+            if job == 0 {
+                thread::sleep(Duration::from_millis(2));
+            }
+            job
+        },
+        |_| count += 1,
+    );
+    assert_eq!(runs.load(Ordering::Relaxed), 200);
+    assert_eq!(count, 200);
+    run_ordered(4, Vec::<u8>::new(), |job| job, |_| panic!("no jobs"));
+}
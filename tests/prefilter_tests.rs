@@ -0,0 +1,56 @@
+use grep_rustico::prefilter::{memchr, Finder};
+use grep_rustico::regex::Regex;
+
+#[test]
+fn test_memchr() {
+    let haystack = b"the quick brown fox jumps over the lazy dog";
+    assert_eq!(memchr(b'q', haystack), Some(4));
+    assert_eq!(memchr(b'g', haystack), Some(42));
+    assert_eq!(memchr(b'z', haystack), Some(37));
+    assert_eq!(memchr(b'!', haystack), None);
+    assert_eq!(memchr(b'a', b""), None);
+}
+
+#[test]
+fn test_finder() {
+    let haystack = b"2024-01-01 INFO user_id=42 ERROR timeout";
+    assert_eq!(Finder::new(b"ERROR").find(haystack), Some(27));
+    assert_eq!(Finder::new(b"user_id=").find(haystack), Some(16));
+    assert_eq!(Finder::new(b"timeout").find(haystack), Some(33));
+    assert_eq!(Finder::new(b"WARN").find(haystack), None);
+    assert_eq!(Finder::new(b"aab").find(b"aaaab"), Some(2));
+    assert_eq!(Finder::new(b"long needle").find(b"short"), None);
+}
+
+#[test]
+fn test_finder_long_needle() {
+    let tail = "a".repeat(40);
+    let needle = format!("x{}", tail);
+    let haystack = format!("{}y{}x{}", tail, tail, tail);
+    assert_eq!(
+        Finder::new(needle.as_bytes()).find(haystack.as_bytes()),
+        Some(81)
+    );
+    assert_eq!(
+        Finder::new(needle.as_bytes()).find(format!("y{}", tail).as_bytes()),
+        None
+    );
+    assert_eq!(
+        Finder::new(tail.as_bytes()).find(haystack.as_bytes()),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_regex_is_candidate() {
+    let regex = Regex::new("ERROR.*id=[[:digit:]]").unwrap();
+    assert!(regex.is_candidate(b"ERROR: bad id=4"));
+    assert!(!regex.is_candidate(b"INFO: all good"));
+
+    let regex = Regex::new("abc|d+f").unwrap();
+    assert!(regex.is_candidate(b"xxdf"));
+    assert!(!regex.is_candidate(b"xxf"));
+
+    let regex = Regex::new("a*b?").unwrap();
+    assert!(regex.is_candidate(b"anything"));
+}
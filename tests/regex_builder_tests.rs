@@ -0,0 +1,165 @@
+use grep_rustico::error::RegexError;
+use grep_rustico::matcher::Matcher;
+use grep_rustico::regex::{Engine, ParseOptions, Regex, DEFAULT_SIZE_LIMIT};
+use grep_rustico::regex_builder::RegexBuilder;
+use grep_rustico::syntax::Syntax;
+
+fn find(regex: &Regex, value: &str) -> Option<(usize, usize)> {
+    regex.find_at(value, 0).map(|m| m.range())
+}
+
+#[test]
+fn test_case_insensitive() {
+    let mut builder = RegexBuilder::new();
+    builder.case_insensitive(true);
+    let regex = builder.build("hello [a-c]x").unwrap();
+    assert_eq!(find(&regex, "say HeLLo BX"), Some((4, 12)));
+    assert!(find(&builder.build("[^a]").unwrap(), "A").is_none());
+    assert!(find(&builder.build("[[:upper:]]").unwrap(), "a").is_some());
+    assert!(find(&builder.build("café").unwrap(), "CAFÉ").is_some());
+    assert!(find(&builder.unicode(false).build("café").unwrap(), "CAFÉ").is_none());
+    assert!(find(&Regex::new("hello").unwrap(), "HELLO").is_none());
+}
+
+#[test]
+fn test_multiline() {
+    let regex = RegexBuilder::new().multiline(true).build("^b|a$").unwrap();
+    assert_eq!(find(&regex, "xa\nb"), Some((1, 2)));
+    assert_eq!(find(&regex, "x\nbz"), Some((2, 3)));
+    assert!(find(&Regex::new("^b|a$").unwrap(), "xa\nb").is_none());
+    assert!(RegexBuilder::new().multiline(true).build("a^b").is_err());
+}
+
+#[test]
+fn test_dot_matches_newline() {
+    assert!(find(&Regex::new("a.b").unwrap(), "a\nb").is_some());
+    let regex = RegexBuilder::new()
+        .dot_matches_newline(false)
+        .build("a.b")
+        .unwrap();
+    assert!(find(&regex, "a\nb").is_none());
+    assert!(find(&regex, "axb").is_some());
+}
+
+#[test]
+fn test_unicode_classes() {
+    let regex = RegexBuilder::new().build("[[:alnum:]]").unwrap();
+    assert!(find(&regex, "é").is_some());
+    let regex = RegexBuilder::new()
+        .unicode(false)
+        .build("[[:alnum:]]")
+        .unwrap();
+    assert!(find(&regex, "é").is_none());
+    assert!(find(&regex, "é1").is_some());
+}
+
+#[test]
+fn test_engine() {
+    let regex = RegexBuilder::new()
+        .syntax(Syntax::Perl)
+        .engine(Engine::PikeVm)
+        .build("(?<word>[a-z]+)=\\d")
+        .unwrap();
+    let found = regex.match_expression("ab cd=1").unwrap().unwrap();
+    assert_eq!(found.range(), (3, 7));
+    assert!(found.name("word").is_none());
+}
+
+#[test]
+fn test_linear_engine_without_match_limit() {
+    let regex = RegexBuilder::new()
+        .engine(Engine::PikeVm)
+        .match_limit(None)
+        .build("(a|a)*(a|a)*[bc]")
+        .unwrap();
+    let value = "a".repeat(30);
+    assert!(find(&regex, &value).is_none());
+    assert_eq!(regex.find_iter(&value).count(), 0);
+    assert_eq!(regex.replace_all(&value, "x"), value);
+}
+
+#[test]
+fn test_size_limit() {
+    assert_eq!(
+        Regex::new("a{1000}{1000}").unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert_eq!(
+        Regex::new("((a{100}){100}){100}").unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert!(RegexBuilder::new().size_limit(10).build("a{20}").is_err());
+    assert!(RegexBuilder::new().size_limit(10).build("a{5}").is_ok());
+}
+
+#[test]
+fn test_size_limit_of_alternatives() {
+    let alternatives = vec!["a{99000}"; 60];
+    assert_eq!(
+        Regex::new(&alternatives.join("|")).unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert_eq!(
+        Regex::new(&format!("({})", alternatives.join("|"))).unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert!(RegexBuilder::new()
+        .size_limit(10)
+        .build("a{5}|b{5}")
+        .is_err());
+    assert!(RegexBuilder::new()
+        .size_limit(12)
+        .build("a{5}|b{5}")
+        .is_ok());
+
+    let regexes = alternatives
+        .iter()
+        .map(|expression| Regex::new(expression).unwrap())
+        .collect();
+    assert_eq!(
+        Regex::union(regexes, DEFAULT_SIZE_LIMIT).unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert_eq!(
+        Matcher::with_options(&alternatives, Syntax::Extended, ParseOptions::default())
+            .unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert!(
+        Matcher::with_options(["a{99000}", "b"], Syntax::Extended, ParseOptions::default()).is_ok()
+    );
+}
+
+#[test]
+fn test_invalid_range_repetitions() {
+    assert_eq!(
+        Regex::new("a{99999999999999999999}").unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    assert_eq!(
+        Regex::new("a{1,99999999999999999999}").unwrap_err(),
+        RegexError::SizeLimitExceeded
+    );
+    for expression in ["a{2,1}", "a{,}", "a{}", "a{1,2,3}"] {
+        assert_eq!(
+            Regex::new(expression).unwrap_err(),
+            RegexError::InvalidRegularExpression,
+            "{expression}"
+        );
+    }
+    assert!(find(&Regex::new("^a{2,2}$").unwrap(), "aa").is_some());
+    assert!(find(&Regex::new("^a{,2}$").unwrap(), "aa").is_some());
+    assert!(find(&Regex::new("^a{,2}$").unwrap(), "aaa").is_none());
+}
+
+#[test]
+fn test_repeated_repetitions() {
+    let regex = Regex::new("^a{2}{3}$").unwrap();
+    assert!(find(&regex, "aaaaaa").is_some());
+    assert!(find(&regex, "aaaaa").is_none());
+    assert!(find(&Regex::new("^a{2}$").unwrap(), "a").is_none());
+    assert_eq!(
+        find(&Regex::new("(ab){2}*c").unwrap(), "abababc"),
+        Some((2, 7))
+    );
+}
@@ -0,0 +1,56 @@
+use grep_rustico::regex_set::RegexSet;
+
+#[test]
+fn test_set_matches() {
+    let set =
+        RegexSet::new(["ERROR", "user_id=[[:digit:]]+", "^start", "end$", "a{2,3}b"]).unwrap();
+    let matches = set.matches("ERROR for user_id=42 at the end");
+    assert_eq!(matches.iter().collect::<Vec<usize>>(), vec![0, 1, 3]);
+    assert!(matches.matched(0));
+    assert!(!matches.matched(2));
+    assert_eq!(matches.len(), 5);
+
+    let matches = set.matches("start: aab");
+    assert_eq!(matches.iter().collect::<Vec<usize>>(), vec![2, 4]);
+
+    assert!(!set.is_match("nothing here"));
+}
+
+#[test]
+fn test_set_first_matches() {
+    let set = RegexSet::new(["ab+", "b", "c.*d", "x|y", "z$"]).unwrap();
+    let matches = set.first_matches("cabbb yd x");
+    let ranges: Vec<Option<(usize, usize)>> = matches
+        .iter()
+        .map(|m| m.as_ref().map(|m| m.range()))
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![Some((1, 5)), Some((2, 3)), Some((0, 8)), Some((6, 7)), None]
+    );
+    assert_eq!(matches[2].as_ref().unwrap().matched, "cabbb yd");
+}
+
+#[test]
+fn test_set_many_patterns() {
+    let patterns: Vec<String> = (0..200).map(|i| format!("alert{}x", i)).collect();
+    let set = RegexSet::new(&patterns).unwrap();
+    let matches = set.matches("alert7x and alert150x");
+    assert_eq!(matches.iter().collect::<Vec<usize>>(), vec![7, 150]);
+}
+
+#[test]
+fn test_set_invalid_pattern() {
+    assert!(RegexSet::new(["a", "b{2,x}"]).is_err());
+}
+
+#[test]
+fn test_set_anchored_wildcard() {
+    let set = RegexSet::new(["^.*foo", "^.*"]).unwrap();
+    let matches = set.first_matches("xxfoo");
+    let ranges: Vec<Option<(usize, usize)>> = matches
+        .iter()
+        .map(|m| m.as_ref().map(|m| m.range()))
+        .collect();
+    assert_eq!(ranges, vec![Some((0, 5)), Some((0, 5))]);
+}
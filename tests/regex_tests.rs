@@ -0,0 +1,50 @@
+use grep_rustico::regex::Regex;
+use grep_rustico::regex_value::RegexVal;
+
+fn find(pattern: &str, value: &str) -> Option<(usize, usize)> {
+    Regex::new(pattern)
+        .unwrap()
+        .match_expression(value)
+        .unwrap()
+        .map(|m| m.range())
+}
+
+#[test]
+fn test_leftmost_match() {
+    assert_eq!(find("b+", "aabbbc"), Some((2, 5)));
+    assert_eq!(find("bc|a", "abc"), Some((0, 1)));
+    assert_eq!(find("a|ab", "ab"), Some((0, 1)));
+    assert_eq!(find("x*", "abc"), Some((0, 0)));
+    assert_eq!(find("d", "abc"), None);
+}
+
+#[test]
+fn test_backtracking() {
+    assert_eq!(find("a*ab", "aaab"), Some((0, 4)));
+    assert_eq!(find("a.*c", "abcbc"), Some((0, 5)));
+    assert_eq!(find("x{2,3}xy", "xxxxy"), Some((0, 5)));
+    assert_eq!(find("a?ab", "ab"), Some((0, 2)));
+}
+
+#[test]
+fn test_anchors() {
+    assert_eq!(find("^ab", "abab"), Some((0, 2)));
+    assert_eq!(find("^b", "ab"), None);
+    assert_eq!(find("ab$", "abab"), Some((2, 4)));
+    assert_eq!(find("^a.*b$", "axxb"), Some((0, 4)));
+    assert_eq!(find("^$", ""), Some((0, 0)));
+}
+
+#[test]
+fn test_only_top_level_parts_are_compiled() {
+    let regex = Regex::new("x((a|b)c)|y").unwrap();
+    assert!(regex
+        .parts
+        .iter()
+        .all(|part| !part.program.insts.is_empty()));
+    let RegexVal::Group { parts, .. } = &regex.parts[0].states[1].value else {
+        panic!("expected a group");
+    };
+    assert!(parts[0].program.insts.is_empty());
+    assert_eq!(find("x((a|b)c)|y", "zxbc"), Some((1, 4)));
+}
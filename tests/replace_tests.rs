@@ -0,0 +1,54 @@
+use grep_rustico::match_result::MatchResult;
+use grep_rustico::regex::Regex;
+use grep_rustico::syntax::Syntax;
+
+#[test]
+fn test_find_iter() {
+    let regex = Regex::new("a+").unwrap();
+    let matches: Vec<(usize, usize)> = regex.find_iter("baaxa").map(|m| m.range()).collect();
+    assert_eq!(matches, vec![(1, 3), (4, 5)]);
+}
+
+#[test]
+fn test_find_iter_empty_matches() {
+    let regex = Regex::new("a*").unwrap();
+    let matches: Vec<(usize, usize)> = regex.find_iter("baa").map(|m| m.range()).collect();
+    assert_eq!(matches, vec![(0, 0), (1, 3), (3, 3)]);
+
+    let regex = Regex::new("^x").unwrap();
+    assert_eq!(regex.find_iter("xxx").count(), 1);
+}
+
+#[test]
+fn test_replace() {
+    let regex = Regex::new("[0-9]+").unwrap();
+    assert_eq!(regex.replace("a1b22c", "#"), "a#b22c");
+    assert_eq!(regex.replace_all("a1b22c", "#"), "a#b#c");
+    assert_eq!(regex.replacen("a1b22c3", 2, "#"), "a#b#c3");
+    assert_eq!(regex.replace_all("abc", "#"), "abc");
+}
+
+#[test]
+fn test_replace_templates() {
+    let regex = Regex::new("([a-z]+)=([0-9]+)").unwrap();
+    assert_eq!(regex.replace_all("x=1, yz=22", "$2=$1"), "1=x, 22=yz");
+    assert_eq!(regex.replace_all("x=1", "${1}_$0"), "x_x=1");
+    assert_eq!(regex.replace_all("x=1", "$$$1"), "$x");
+    assert_eq!(regex.replace_all("x=1", "$1a"), "");
+    assert_eq!(regex.replace_all("x=1", "$9 $ {"), " $ {");
+
+    let regex = Regex::with_syntax("(?<key>[a-z]+)=(?<value>[0-9]+)", Syntax::Perl).unwrap();
+    assert_eq!(
+        regex.replace_all("x=1, yz=22", "${value}:$key"),
+        "1:x, 22:yz"
+    );
+}
+
+#[test]
+fn test_replace_with_closure() {
+    let regex = Regex::new("[0-9]+").unwrap();
+    let doubled = regex.replace_all("a1b22c", |m: &MatchResult| {
+        (m.matched.parse::<u32>().unwrap() * 2).to_string()
+    });
+    assert_eq!(doubled, "a2b44c");
+}
@@ -0,0 +1,91 @@
+use grep_rustico::regex::Regex;
+use grep_rustico::segment::{Segment, SegmentKind};
+
+#[test]
+fn test_split() {
+    let regex = Regex::new("[,;][[:space:]]*").unwrap();
+    let fields: Vec<&str> = regex.split("a, b;c,  d").collect();
+    assert_eq!(fields, vec!["a", "b", "c", "d"]);
+
+    let fields: Vec<&str> = regex.split(",a,").collect();
+    assert_eq!(fields, vec!["", "a", ""]);
+
+    let fields: Vec<&str> = regex.split("").collect();
+    assert_eq!(fields, vec![""]);
+}
+
+#[test]
+fn test_split_empty_matches() {
+    let regex = Regex::new("x*").unwrap();
+    let pieces: Vec<&str> = regex.split("abc").collect();
+    assert_eq!(pieces, vec!["", "a", "b", "c", ""]);
+
+    let regex = Regex::new("^a").unwrap();
+    let pieces: Vec<&str> = regex.split("aba").collect();
+    assert_eq!(pieces, vec!["", "ba"]);
+}
+
+#[test]
+fn test_splitn() {
+    let regex = Regex::new(",").unwrap();
+    let pieces: Vec<&str> = regex.splitn("a,b,c,d", 2).collect();
+    assert_eq!(pieces, vec!["a", "b,c,d"]);
+    let pieces: Vec<&str> = regex.splitn("a,b", 5).collect();
+    assert_eq!(pieces, vec!["a", "b"]);
+    assert_eq!(regex.splitn("a,b", 1).collect::<Vec<&str>>(), vec!["a,b"]);
+    assert_eq!(regex.splitn("a,b", 0).count(), 0);
+}
+
+#[test]
+fn test_tokenize() {
+    let regex = Regex::new("[0-9]+").unwrap();
+    let segments: Vec<Segment> = regex.tokenize("ab12c3").collect();
+    assert_eq!(
+        segments,
+        vec![
+            Segment {
+                kind: SegmentKind::Unmatched,
+                start: 0,
+                end: 2,
+                text: "ab",
+            },
+            Segment {
+                kind: SegmentKind::Matched,
+                start: 2,
+                end: 4,
+                text: "12",
+            },
+            Segment {
+                kind: SegmentKind::Unmatched,
+                start: 4,
+                end: 5,
+                text: "c",
+            },
+            Segment {
+                kind: SegmentKind::Matched,
+                start: 5,
+                end: 6,
+                text: "3",
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_empty_matches() {
+    let regex = Regex::new("b*").unwrap();
+    let segments: Vec<(SegmentKind, &str)> = regex
+        .tokenize("abb")
+        .map(|segment| (segment.kind, segment.text))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            (SegmentKind::Matched, ""),
+            (SegmentKind::Unmatched, "a"),
+            (SegmentKind::Matched, "bb"),
+            (SegmentKind::Matched, ""),
+        ]
+    );
+    assert_eq!(regex.tokenize("").count(), 1);
+}
@@ -0,0 +1,192 @@
+use grep_rustico::regex::Regex;
+use grep_rustico::regex_builder::RegexBuilder;
+use grep_rustico::syntax::Syntax;
+use std::io::{self, Read};
+
+/// A reader that returns at most one byte per read, like a slow pipe.
+struct OneByteReader<'a>(&'a [u8]);
+
+impl Read for OneByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+fn stream_ranges(regex: &Regex, value: &str, chunk_size: usize) -> Vec<(usize, usize)> {
+    regex
+        .stream_find_iter_with_chunk_size(value.as_bytes(), chunk_size)
+        .map(|found| found.unwrap().range())
+        .collect()
+}
+
+fn ranges(regex: &Regex, value: &str) -> Vec<(usize, usize)> {
+    regex.find_iter(value).map(|found| found.range()).collect()
+}
+
+#[test]
+fn test_absolute_offsets() {
+    let regex = Regex::new("ab+c").unwrap();
+    let value = "xxabbbc yy abc\nabbbbbbbbbbc";
+    let matches: Vec<_> = regex
+        .stream_find_iter_with_chunk_size(value.as_bytes(), 4)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(matches.len(), 3);
+    assert_eq!(matches[0].range(), (2, 7));
+    assert_eq!(matches[1].range(), (11, 14));
+    assert_eq!(matches[2].range(), (15, 27));
+    assert_eq!(matches[2].matched, b"abbbbbbbbbbc");
+}
+
+#[test]
+fn test_same_matches_as_find_iter_with_every_chunk_size() {
+    let perl = |expression| Regex::with_syntax(expression, Syntax::Perl).unwrap();
+    let multiline = RegexBuilder::new()
+        .syntax(Syntax::Perl)
+        .multiline(true)
+        .dot_matches_newline(false)
+        .build("^b|c$")
+        .unwrap();
+    let dot_stops_at_newline = RegexBuilder::new()
+        .dot_matches_newline(false)
+        .build("o.*o")
+        .unwrap();
+    let cases = [
+        (perl("ab|a.c"), "abc axc ab a"),
+        (perl("a*"), "baaab"),
+        (perl("^b|c$"), "bc"),
+        (multiline, "ab\nbc\nc"),
+        (dot_stops_at_newline, "foo\nboo"),
+        (perl("o.*o"), "foo\nboo\n"),
+        (perl("é+"), "ééaé"),
+        (perl("x?"), "aé"),
+        (perl("[0-9]{3}-[0-9]{4}"), "call 555-1234 or 555-98765"),
+    ];
+    for (regex, value) in &cases {
+        for chunk_size in 1..=8 {
+            assert_eq!(
+                stream_ranges(regex, value, chunk_size),
+                ranges(regex, value),
+                "{value:?} with chunks of {chunk_size}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_match_across_lines() {
+    let regex = Regex::new("end\nstart").unwrap();
+    let found: Vec<_> = regex
+        .stream_find_iter(OneByteReader(b"the end\nstart again"))
+        .map(|found| found.unwrap().range())
+        .collect();
+    assert_eq!(found, vec![(4, 13)]);
+}
+
+#[test]
+fn test_invalid_utf8_stream() {
+    let regex = Regex::new("au l[a-z]+").unwrap();
+    let found: Vec<_> = regex
+        .stream_find_iter(OneByteReader(b"caf\xe9 au lait, \xff au lit"))
+        .map(|found| found.unwrap().matched)
+        .collect();
+    assert_eq!(found, vec![b"au lait".to_vec(), b"au lit".to_vec()]);
+}
+
+#[test]
+fn test_empty_stream() {
+    let regex = Regex::new("a").unwrap();
+    assert_eq!(regex.stream_find_iter(io::empty()).count(), 0);
+    let regex = Regex::new("a*").unwrap();
+    assert_eq!(stream_ranges(&regex, "", 4), vec![(0, 0)]);
+}
+
+#[test]
+fn test_matched_lines() {
+    let regex = Regex::with_syntax("b\\nc|e", Syntax::Perl).unwrap();
+    let value = b"aaa\nbbb\nccc\nded\n";
+    let mut matches = regex
+        .stream_find_iter_with_chunk_size(&value[..], 3)
+        .whole_lines();
+    let mut lines = vec![];
+    while let Some(found) = matches.next() {
+        let found = found.unwrap();
+        let (start, content) = matches.matched_lines(&found).unwrap();
+        lines.push((found.range(), start, content.to_vec()));
+    }
+    assert_eq!(
+        lines,
+        vec![
+            ((6, 9), 4, b"bbb\nccc".to_vec()),
+            ((13, 14), 12, b"ded".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_line_numbers() {
+    let regex = Regex::with_syntax("b\\nc|e|x", Syntax::Perl).unwrap();
+    let value = "aaa\nbbb\nccc\nded\n\n\nyyy\nx\n";
+    for chunk_size in [1, 3, 64] {
+        let mut matches = regex
+            .stream_find_iter_with_chunk_size(value.as_bytes(), chunk_size)
+            .whole_lines();
+        let mut lines = vec![];
+        while let Some(found) = matches.next() {
+            let found = found.unwrap();
+            lines.push(matches.line_number(found.start));
+        }
+        assert_eq!(lines, vec![2, 4, 8], "chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn test_dot_matches_newline_buffers_the_stream() {
+    let value = "ab\n".repeat(2_000);
+    let bytes_left = |regex: &Regex| {
+        let mut reader = value.as_bytes();
+        let first = regex
+            .stream_find_iter_with_chunk_size(&mut reader, 64)
+            .next()
+            .unwrap()
+            .unwrap();
+        (first.range(), reader.len())
+    };
+    let (range, left) = bytes_left(&Regex::new("a.*").unwrap());
+    assert_eq!((range, left), ((0, value.len()), 0));
+
+    let regex = RegexBuilder::new()
+        .dot_matches_newline(false)
+        .build("a.*")
+        .unwrap();
+    let (range, left) = bytes_left(&regex);
+    assert_eq!(range, (0, 2));
+    assert!(left > value.len() - 1024);
+}
+
+#[test]
+fn test_buffer_limit() {
+    let regex = Regex::new("ERROR[^Z]*Z").unwrap();
+    let value = format!("ERROR {}", "abc def\n".repeat(125_000));
+    let mut reader = value.as_bytes();
+    let error = regex
+        .stream_find_iter(&mut reader)
+        .buffer_limit(1 << 18)
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::OutOfMemory);
+    assert!(reader.len() > value.len() - (1 << 19));
+
+    let value = value + "Z";
+    let found: Vec<_> = regex
+        .stream_find_iter(value.as_bytes())
+        .map(|found| found.unwrap().range())
+        .collect();
+    assert_eq!(found, vec![(0, value.len())]);
+}
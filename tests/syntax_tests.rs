@@ -0,0 +1,151 @@
+use grep_rustico::error::RegexError;
+use grep_rustico::regex::{ParseOptions, Regex, MAX_NESTING_DEPTH};
+use grep_rustico::syntax::Syntax;
+
+fn find(expression: &str, syntax: Syntax, value: &str) -> Option<String> {
+    Regex::with_syntax(expression, syntax)
+        .unwrap()
+        .match_expression(value)
+        .unwrap()
+        .map(|m| m.matched)
+}
+
+#[test]
+fn test_extended_groups() {
+    let e = Syntax::Extended;
+    assert_eq!(find("(ab)+c", e, "xababcx"), Some("ababc".to_string()));
+    assert_eq!(find("a(b|cd)e", e, "acde"), Some("acde".to_string()));
+    assert_eq!(find("(cat|dog)s?$", e, "hotdogs"), Some("dogs".to_string()));
+    assert_eq!(find("^(a|b){2}$", e, "ab"), Some("ab".to_string()));
+    assert_eq!(find("^(a|b){2}$", e, "abc"), None);
+    assert_eq!(find("(^start|end$)", e, "the end"), Some("end".to_string()));
+    assert_eq!(find("(a*)*b", e, "aab"), Some("aab".to_string()));
+    assert_eq!(find("x(a|)y", e, "xy"), Some("xy".to_string()));
+}
+
+#[test]
+fn test_leftmost_match() {
+    let e = Syntax::Extended;
+    assert_eq!(find("b|a", e, "ab"), Some("a".to_string()));
+    assert_eq!(find("a.*b", e, "xaxbxb"), Some("axbxb".to_string()));
+}
+
+#[test]
+fn test_basic_syntax() {
+    let b = Syntax::Basic;
+    assert_eq!(find("\\(ab\\)*c", b, "ababc"), Some("ababc".to_string()));
+    assert_eq!(find("a\\{2,3\\}", b, "caaaa"), Some("aaa".to_string()));
+    assert_eq!(find("a+b?", b, "a+b?"), Some("a+b?".to_string()));
+    assert_eq!(find("a|b", b, "b"), None);
+    assert_eq!(find("a\\|b", b, "b"), Some("b".to_string()));
+    assert_eq!(find("ab\\+", b, "abbb"), Some("abbb".to_string()));
+    assert_eq!(find("ab\\?c", b, "ac"), Some("ac".to_string()));
+    assert_eq!(find("(x){1}", b, "(x){1}"), Some("(x){1}".to_string()));
+    assert_eq!(find("*a", b, "b*a"), Some("*a".to_string()));
+    assert_eq!(find("a^b$c", b, "a^b$c"), Some("a^b$c".to_string()));
+    assert_eq!(find("^ab$", b, "ab"), Some("ab".to_string()));
+}
+
+#[test]
+fn test_same_representation() {
+    let basic = Regex::with_syntax("^\\(a\\|bc\\)\\{1,2\\}d*$", Syntax::Basic).unwrap();
+    let extended = Regex::with_syntax("^(a|bc){1,2}d*$", Syntax::Extended).unwrap();
+    assert_eq!(
+        format!("{:?}", basic.parts),
+        format!("{:?}", extended.parts)
+    );
+}
+
+#[test]
+fn test_unmatched_parenthesis() {
+    assert!(Regex::new("(ab").is_err());
+    assert!(Regex::new("ab)").is_err());
+    assert!(Regex::with_syntax("\\(ab", Syntax::Basic).is_err());
+    assert!(Regex::new("[ab").is_err());
+}
+
+#[test]
+fn test_nesting_limit() {
+    let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(Regex::new(&nested(MAX_NESTING_DEPTH)).is_ok());
+    assert_eq!(
+        Regex::new(&nested(MAX_NESTING_DEPTH + 1)).unwrap_err(),
+        RegexError::NestingLimitExceeded
+    );
+    assert_eq!(
+        Regex::new(&nested(30_000)).unwrap_err(),
+        RegexError::NestingLimitExceeded
+    );
+    assert_eq!(
+        Regex::new(&format!("a{}", "*".repeat(30_000))).unwrap_err(),
+        RegexError::NestingLimitExceeded
+    );
+    assert_eq!(
+        Regex::new(&format!("(a{})", "*".repeat(MAX_NESTING_DEPTH + 1))).unwrap_err(),
+        RegexError::NestingLimitExceeded
+    );
+}
+
+#[test]
+fn test_perl_syntax() {
+    let p = Syntax::Perl;
+    assert_eq!(find("(?:ab)+c", p, "xababc"), Some("ababc".to_string()));
+    assert_eq!(
+        find("(?<year>\\d{4})-\\d\\d", p, "on 2024-05"),
+        Some("2024-05".to_string())
+    );
+    assert_eq!(find("(?P<x>a)b", p, "ab"), Some("ab".to_string()));
+    assert_eq!(find("\\x41\\u{00e9}", p, "xAé"), Some("Aé".to_string()));
+    assert_eq!(find("a\\tb", p, "a\tb"), Some("a\tb".to_string()));
+    assert_eq!(find("\\Qa.b*\\E+", p, "a.b**"), Some("a.b**".to_string()));
+    assert_eq!(find("\\Qa.b", p, "axb"), None);
+    assert_eq!(find("\\Aab", p, "cab"), None);
+    assert_eq!(find("ab\\z", p, "cab"), Some("ab".to_string()));
+    assert_eq!(find("\\w+\\s\\W", p, "hi !"), Some("hi !".to_string()));
+}
+
+#[test]
+fn test_perl_same_representation() {
+    let perl = Regex::with_syntax("^(?:a|bc){1,2}\\x64*$", Syntax::Perl).unwrap();
+    let extended = Regex::with_syntax("^(a|bc){1,2}d*$", Syntax::Extended).unwrap();
+    assert_eq!(perl.parts.len(), extended.parts.len());
+    assert_eq!(
+        find("^(?:a|bc){1,2}\\x64*$", Syntax::Perl, "bcadd"),
+        Some("bcadd".to_string())
+    );
+}
+
+#[test]
+fn test_perl_invalid_groups() {
+    let p = Syntax::Perl;
+    assert!(Regex::with_syntax("(?<a>x)(?<a>y)", p).is_err());
+    assert!(Regex::with_syntax("(?<>x)", p).is_err());
+    assert!(Regex::with_syntax("(?x)", p).is_err());
+    assert!(Regex::with_syntax("\\k", p).is_err());
+}
+
+#[test]
+fn test_bracket_ranges_with_other_chars() {
+    let e = Syntax::Extended;
+    assert_eq!(find("[a-c_x]+", e, "zab_xcd"), Some("ab_xc".to_string()));
+    assert_eq!(find("[0-9A-F]+", e, "0x1F"), Some("0".to_string()));
+    assert_eq!(find("x[0-9A-F]+", e, "0x1F"), Some("x1F".to_string()));
+    assert_eq!(find("[a-]+", e, "b-a"), Some("-a".to_string()));
+    assert!(Regex::with_syntax("[_z-a]", e).is_err());
+}
+
+#[test]
+fn test_newline_escape_in_multiline_mode() {
+    let options = ParseOptions {
+        multiline: true,
+        ..ParseOptions::default()
+    };
+    for (expression, syntax) in [("a\\nb", Syntax::Basic), ("a\\nb", Syntax::Extended)] {
+        let regex = Regex::with_options(expression, syntax, options).unwrap();
+        assert_eq!(regex.find_at("xa\nb", 0).unwrap().range(), (1, 4));
+    }
+    assert_eq!(
+        find("a\\nb", Syntax::Extended, "anb"),
+        Some("anb".to_string())
+    );
+}
@@ -0,0 +1,116 @@
+use grep_rustico::glob::Glob;
+use grep_rustico::walker::Walker;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Creates an empty directory for a test in the temporary directory.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("grep-rustico-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn walk(walker: &Walker, root: &Path) -> Vec<String> {
+    walker
+        .walk(root)
+        .map(|entry| {
+            let path = entry.unwrap();
+            path.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_sorted_files() {
+    let root = Path::new("texts/tree");
+    assert_eq!(
+        walk(&Walker::new(), root),
+        vec![
+            "a.txt",
+            "sub/b.txt",
+            "sub/d.rs",
+            "sub/deep/c.txt",
+            "sub/empty.txt"
+        ]
+    );
+}
+
+#[test]
+fn test_max_depth() {
+    let root = Path::new("texts/tree");
+    let mut walker = Walker::new();
+    assert_eq!(walk(walker.max_depth(Some(0)), root), Vec::<String>::new());
+    assert_eq!(walk(walker.max_depth(Some(1)), root), vec!["a.txt"]);
+    assert_eq!(
+        walk(walker.max_depth(Some(2)), root),
+        vec!["a.txt", "sub/b.txt", "sub/d.rs", "sub/empty.txt"]
+    );
+}
+
+#[test]
+fn test_globs() {
+    let root = Path::new("texts/tree");
+    let globs = |patterns: &[&str]| -> Vec<Glob> {
+        patterns
+            .iter()
+            .map(|pattern| Glob::new(pattern).unwrap())
+            .collect()
+    };
+    let mut walker = Walker::new();
+    walker.include(globs(&["*.rs", "a.*"]));
+    assert_eq!(walk(&walker, root), vec!["a.txt", "sub/d.rs"]);
+
+    let mut walker = Walker::new();
+    walker.exclude(globs(&["e*", "sub/*/*.txt"]));
+    assert_eq!(walk(&walker, root), vec!["a.txt", "sub/b.txt", "sub/d.rs"]);
+
+    let mut walker = Walker::new();
+    walker.exclude_dir(globs(&["deep"]));
+    assert_eq!(
+        walk(&walker, root),
+        vec!["a.txt", "sub/b.txt", "sub/d.rs", "sub/empty.txt"]
+    );
+    assert_eq!(
+        walk(walker.exclude_dir(globs(&["s?b"])), root),
+        vec!["a.txt"]
+    );
+}
+
+#[test]
+fn test_file_root() {
+    let files: Vec<PathBuf> = Walker::new()
+        .walk("texts/test.txt")
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(files, vec![PathBuf::from("texts/test.txt")]);
+    assert!(Walker::new().walk("texts/missing").next().unwrap().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symbolic_links() {
+    use std::os::unix::fs::symlink;
+
+    let root = temp_dir("links");
+    fs::create_dir(root.join("dir")).unwrap();
+    fs::write(root.join("dir/file.txt"), "hola\n").unwrap();
+    symlink(root.join("dir/file.txt"), root.join("link.txt")).unwrap();
+    symlink(&root, root.join("dir/loop")).unwrap();
+
+    assert_eq!(walk(&Walker::new(), &root), vec!["dir/file.txt"]);
+
+    let mut walker = Walker::new();
+    walker.follow_links(true);
+    let entries: Vec<_> = walker.walk(&root).collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].as_ref().unwrap(), &root.join("dir/file.txt"));
+    let err = entries[1].as_ref().unwrap_err();
+    assert_eq!(err.path, root.join("dir/loop"));
+    assert_eq!(err.error.to_string(), "recursive directory loop");
+    assert_eq!(entries[2].as_ref().unwrap(), &root.join("link.txt"));
+    fs::remove_dir_all(&root).unwrap();
+}